@@ -0,0 +1,85 @@
+use std::fmt;
+use std::io::{self, BufRead};
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    /// A line that is neither a number, a comment nor blank. Line numbers start from 1.
+    InvalidNumber { line: usize, text: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "could not read input: {}", e),
+            ParseError::InvalidNumber { line, text } => write!(f, "line {}: invalid number {:?}", line, text),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
+/// Streams `(line number, value)` pairs from the reader. Surrounding whitespace is ignored, as are
+/// blank lines and comment lines starting with `#`. Collecting into a `Result` stops at the first
+/// line that fails to parse, e.g. `Result<Vec<(usize, i64)>, ParseError>` or an `ExpenseReport`.
+pub struct Entries<R> {
+    lines: io::Lines<R>,
+    line: usize,
+}
+
+pub fn read_entries<R: BufRead>(reader: R) -> Entries<R> {
+    Entries { lines: reader.lines(), line: 0 }
+}
+
+impl<R: BufRead> Iterator for Entries<R> {
+    type Item = Result<(usize, i64), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for line in &mut self.lines {
+            self.line += 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(ParseError::from(e))),
+            };
+
+            let text = line.trim();
+            if text.is_empty() || text.starts_with('#') { continue; }
+
+            return Some(i64::from_str(text)
+                .map(|value| (self.line, value))
+                .map_err(|_| ParseError::InvalidNumber { line: self.line, text: line.to_owned() }));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_entries() {
+        let input = "# expense report\n1721\n979  \n\n  366\r\n# end\n";
+        let entries: Vec<(usize, i64)> = read_entries(input.as_bytes()).map(|e| e.unwrap()).collect();
+        assert_eq!(entries, vec![(2, 1721), (3, 979), (5, 366)]);
+    }
+
+    #[test]
+    fn test_read_entries_invalid_line() {
+        match read_entries("1721\n\n97a9\n366".as_bytes()).collect::<Result<Vec<_>, _>>() {
+            Err(ParseError::InvalidNumber { line, text }) => {
+                assert_eq!(line, 3);
+                assert_eq!(text, "97a9");
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::report::{ExpenseReport, Operand};
+
+/// Finds `k` entries of the report (each line used at most once) that add up to `target`.
+///
+/// Small `k` are handled with sorting and two pointers, larger ones are split in half and solved
+/// with meet-in-the-middle. Returns the operands in ascending order of value. Sums are computed
+/// in `i128`, so values near the ends of the `i64` range cannot overflow.
+pub fn find_k_sum(report: &ExpenseReport, k: usize, target: i64) -> Option<Vec<Operand>> {
+    let sorted = report.operands(k);
+
+    let indices = match k {
+        0 => if target == 0 { Some(Vec::new()) } else { None },
+        1 => sorted.iter().position(|e| e.value == target).map(|i| vec![i]),
+        2 => find_pair(&sorted, 0, i128::from(target)).map(|(i, j)| vec![i, j]),
+        3 => find_triple(&sorted, target),
+        _ => find_meet_in_the_middle(&sorted, k, target),
+    };
+
+    indices.map(|v| v.into_iter().map(|i| sorted[i]).collect())
+}
+
+/// Two pointer search over `sorted[start..]`, returns indices of the pair.
+fn find_pair(sorted: &[Operand], start: usize, target: i128) -> Option<(usize, usize)> {
+    if sorted.len() < 2 { return None; }
+    let (mut lo, mut hi) = (start, sorted.len() - 1);
+
+    while lo < hi {
+        let sum = i128::from(sorted[lo].value) + i128::from(sorted[hi].value);
+        if sum == target {
+            return Some((lo, hi));
+        } else if sum < target {
+            lo += 1;
+        } else {
+            hi -= 1;
+        }
+    }
+
+    None
+}
+
+fn find_triple(sorted: &[Operand], target: i64) -> Option<Vec<usize>> {
+    for i in 0..sorted.len() {
+        if i > 0 && sorted[i].value == sorted[i - 1].value { continue; }
+        if let Some((j, l)) = find_pair(sorted, i + 1, i128::from(target) - i128::from(sorted[i].value)) {
+            return Some(vec![i, j, l]);
+        }
+    }
+
+    None
+}
+
+/// Every k-combination of sorted indices can be cut into a left half whose indices all come before
+/// the right half, so it is enough to remember the left half with the smallest last index per sum.
+fn find_meet_in_the_middle(sorted: &[Operand], k: usize, target: i64) -> Option<Vec<usize>> {
+    let left_size = k / 2;
+    let right_size = k - left_size;
+    let sum_of = |combination: &[usize]| combination.iter().map(|&i| i128::from(sorted[i].value)).sum::<i128>();
+
+    let mut left: HashMap<i128, Vec<usize>> = HashMap::new();
+    for combination in Combinations::new(sorted.len(), left_size) {
+        let (sum, last) = (sum_of(&combination), *combination.last().unwrap());
+        match left.get(&sum) {
+            Some(existing) if *existing.last().unwrap() <= last => {}
+            _ => { left.insert(sum, combination); }
+        }
+    }
+
+    for combination in Combinations::new(sorted.len(), right_size) {
+        if let Some(left_half) = left.get(&(i128::from(target) - sum_of(&combination))) {
+            if left_half.last().unwrap() < &combination[0] {
+                return Some(left_half.iter().chain(combination.iter()).cloned().collect());
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns every distinct (by value) combination of `k` entries summing to `target`, in ascending
+/// lexicographical order of values. Each combination uses the earliest lines of its values.
+pub fn k_sums(report: &ExpenseReport, k: usize, target: i64, limit: Option<usize>) -> impl Iterator<Item=Vec<Operand>> {
+    KSums::new(report.operands(k), k, target).take(limit.unwrap_or(usize::MAX))
+}
+
+/// Enumerates the first `k - 2` operands with an odometer that skips repeated values, then finds
+/// all matching pairs for that prefix with two pointers.
+struct KSums {
+    sorted: Vec<Operand>,
+    k: usize,
+    target: i64,
+    prefix: Vec<usize>,
+    pending: VecDeque<Vec<usize>>,
+    done: bool,
+}
+
+impl KSums {
+    fn new(sorted: Vec<Operand>, k: usize, target: i64) -> Self {
+        let mut pending = VecDeque::new();
+        let mut done = true;
+
+        match k {
+            0 => if target == 0 { pending.push_back(Vec::new()); },
+            1 => pending.extend(sorted.iter().position(|e| e.value == target).map(|i| vec![i])),
+            _ => done = sorted.len() < k,
+        }
+
+        let prefix = (0..k.saturating_sub(2)).collect();
+        KSums { sorted, k, target, prefix, pending, done }
+    }
+
+    fn find_pairs(&mut self) {
+        let start = self.prefix.last().map_or(0, |&e| e + 1);
+        let prefix_sum: i128 = self.prefix.iter().map(|&i| i128::from(self.sorted[i].value)).sum();
+        let remaining = i128::from(self.target) - prefix_sum;
+        let sorted = &self.sorted;
+        let (mut lo, mut hi) = (start, sorted.len() - 1);
+
+        while lo < hi {
+            let sum = i128::from(sorted[lo].value) + i128::from(sorted[hi].value);
+            if sum < remaining {
+                lo += 1;
+            } else if sum > remaining {
+                hi -= 1;
+            } else {
+                let mut first_hi = hi;
+                while first_hi - 1 > lo && sorted[first_hi - 1].value == sorted[hi].value { first_hi -= 1; }
+
+                let mut indices = self.prefix.clone();
+                indices.extend([lo, first_hi].iter());
+                self.pending.push_back(indices);
+
+                let (lo_value, hi_value) = (sorted[lo].value, sorted[hi].value);
+                while lo < hi && sorted[lo].value == lo_value { lo += 1; }
+                while hi > lo && sorted[hi].value == hi_value { hi -= 1; }
+            }
+        }
+    }
+
+    /// Moves the prefix to the next combination with distinct values, returns false when exhausted.
+    fn advance_prefix(&mut self) -> bool {
+        let (n, r) = (self.sorted.len(), self.prefix.len());
+        for level in (0..r).rev() {
+            let limit = n - (self.k - level);
+            let mut i = self.prefix[level] + 1;
+            while i <= limit && self.sorted[i].value == self.sorted[i - 1].value { i += 1; }
+
+            if i <= limit {
+                self.prefix[level] = i;
+                for j in level + 1..r {
+                    self.prefix[j] = self.prefix[j - 1] + 1;
+                }
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+impl Iterator for KSums {
+    type Item = Vec<Operand>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && !self.done {
+            self.find_pairs();
+            self.done = !self.advance_prefix();
+        }
+
+        self.pending.pop_front().map(|indices| indices.into_iter().map(|i| self.sorted[i]).collect())
+    }
+}
+
+/// Number of solutions and the range of their products.
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub min_product: Option<i128>,
+    pub max_product: Option<i128>,
+}
+
+/// Products are computed in `i128` and saturate instead of overflowing.
+pub fn product(operands: &[Operand]) -> i128 {
+    operands.iter().fold(1i128, |acc, e| acc.saturating_mul(e.value as i128))
+}
+
+pub fn summarize(solutions: impl Iterator<Item=Vec<Operand>>) -> Summary {
+    solutions.fold(Summary::default(), |acc, operands| {
+        let p = product(&operands);
+        Summary {
+            count: acc.count + 1,
+            min_product: Some(acc.min_product.map_or(p, |e| e.min(p))),
+            max_product: Some(acc.max_product.map_or(p, |e| e.max(p))),
+        }
+    })
+}
+
+/// Iterates over all `r` sized index combinations of `0..n` in lexicographical order.
+struct Combinations {
+    n: usize,
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl Combinations {
+    fn new(n: usize, r: usize) -> Self {
+        Combinations { n, indices: (0..r).collect(), done: r > n || r == 0 }
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done { return None; }
+        let result = self.indices.clone();
+
+        let r = self.indices.len();
+        match (0..r).rev().find(|&i| self.indices[i] != i + self.n - r) {
+            Some(i) => {
+                self.indices[i] += 1;
+                for j in i + 1..r {
+                    self.indices[j] = self.indices[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [i64; 6] = [1721, 979, 366, 299, 675, 1456];
+
+    /// Numbers the values from line 1 onwards.
+    fn new_report(data: &[i64]) -> ExpenseReport {
+        data.iter().enumerate().map(|(i, &value)| (i + 1, value)).collect()
+    }
+
+    fn values(data: &[i64], k: usize, target: i64) -> Option<Vec<i64>> {
+        find_k_sum(&new_report(data), k, target).map(|v| v.iter().map(|e| e.value).collect())
+    }
+
+    fn lines(data: &[i64], k: usize, target: i64) -> Option<Vec<usize>> {
+        find_k_sum(&new_report(data), k, target).map(|v| v.iter().map(|e| e.line).collect())
+    }
+
+    #[test]
+    fn test_combinations() {
+        let all: Vec<Vec<usize>> = Combinations::new(4, 2).collect();
+        assert_eq!(all, vec![vec![0, 1], vec![0, 2], vec![0, 3], vec![1, 2], vec![1, 3], vec![2, 3]]);
+        assert_eq!(Combinations::new(2, 3).count(), 0);
+    }
+
+    #[test]
+    fn test_find_k_sum() {
+        assert_eq!(values(&EXAMPLE, 2, 2020), Some(vec![299, 1721]));
+        assert_eq!(lines(&EXAMPLE, 2, 2020), Some(vec![4, 1]));
+        assert_eq!(values(&EXAMPLE, 3, 2020), Some(vec![366, 675, 979]));
+        assert_eq!(lines(&EXAMPLE, 3, 2020), Some(vec![3, 5, 2]));
+        assert_eq!(values(&EXAMPLE, 1, 366), Some(vec![366]));
+        assert_eq!(values(&EXAMPLE, 2, 1), None);
+    }
+
+    #[test]
+    fn test_find_k_sum_meet_in_the_middle() {
+        assert_eq!(values(&EXAMPLE, 4, 299 + 366 + 675 + 1456), Some(vec![299, 366, 675, 1456]));
+        assert_eq!(values(&EXAMPLE, 5, 1721 + 979 + 366 + 299 + 675), Some(vec![299, 366, 675, 979, 1721]));
+        assert_eq!(values(&EXAMPLE, 7, 0), None);
+    }
+
+    #[test]
+    fn test_find_k_sum_duplicates() {
+        assert_eq!(values(&[1010, 5], 2, 2020), None);
+        assert_eq!(lines(&[1010, 5, 1010], 2, 2020), Some(vec![1, 3]));
+        assert_eq!(lines(&[7, 1010, 1010, 1010, 5], 3, 3030), Some(vec![2, 3, 4]));
+        assert_eq!(values(&[2, 2, 2, 1], 4, 8), None);
+        assert_eq!(lines(&[2, 2, 2, 1, 2], 4, 8), Some(vec![1, 2, 3, 5]));
+    }
+
+    fn all_values(data: &[i64], k: usize, target: i64) -> Vec<Vec<i64>> {
+        k_sums(&new_report(data), k, target, None)
+            .map(|v| v.iter().map(|e| e.value).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_k_sums() {
+        assert_eq!(all_values(&EXAMPLE, 2, 2020), vec![vec![299, 1721]]);
+        assert_eq!(all_values(&[1, 2, 3, 4, 5], 2, 6), vec![vec![1, 5], vec![2, 4]]);
+        assert_eq!(all_values(&[1, 2, 3, 4, 5, 6], 3, 10), vec![vec![1, 3, 6], vec![1, 4, 5], vec![2, 3, 5]]);
+        assert_eq!(all_values(&[1, 2, 3, 4, 5], 4, 12), vec![vec![1, 2, 4, 5]]);
+        assert_eq!(all_values(&[3, 3], 1, 3), vec![vec![3]]);
+        assert_eq!(all_values(&[3, 3], 0, 0), vec![Vec::<i64>::new()]);
+        assert!(all_values(&[1, 2], 3, 3).is_empty());
+    }
+
+    #[test]
+    fn test_k_sums_duplicates() {
+        let data = [1010, 3, 1010, 1, 1010, 2019, 1, 2];
+        assert_eq!(all_values(&data, 2, 2020), vec![vec![1, 2019], vec![1010, 1010]]);
+        assert_eq!(all_values(&data, 3, 2022), vec![vec![1, 2, 2019], vec![2, 1010, 1010]]);
+        assert_eq!(all_values(&[2, 2, 2, 2, 2], 4, 8), vec![vec![2, 2, 2, 2]]);
+
+        let lines: Vec<Vec<usize>> = k_sums(&new_report(&data), 2, 2020, None)
+            .map(|v| v.iter().map(|e| e.line).collect())
+            .collect();
+        assert_eq!(lines, vec![vec![4, 6], vec![1, 3]]);
+    }
+
+    #[test]
+    fn test_k_sums_limit() {
+        let report = new_report(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(k_sums(&report, 3, 10, Some(2)).count(), 2);
+        assert_eq!(k_sums(&report, 3, 10, Some(5)).count(), 3);
+    }
+
+    #[test]
+    fn test_extreme_values() {
+        let data = [i64::MAX, i64::MAX, i64::MIN, -1, 1];
+        assert_eq!(values(&data, 2, i64::MAX - 1), Some(vec![-1, i64::MAX]));
+        assert_eq!(values(&data, 2, -2), None);
+        assert_eq!(values(&data, 3, i64::MAX), Some(vec![-1, 1, i64::MAX]));
+        assert_eq!(values(&data, 4, -1), Some(vec![i64::MIN, -1, 1, i64::MAX]));
+        assert_eq!(all_values(&data, 2, 0), vec![vec![-1, 1]]);
+        assert_eq!(all_values(&data, 3, 0), vec![vec![i64::MIN, 1, i64::MAX]]);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let report = new_report(&[1, 2, 3, 4, 5, 6]);
+        let summary = summarize(k_sums(&report, 3, 10, None));
+        assert_eq!(summary, Summary { count: 3, min_product: Some(18), max_product: Some(30) });
+        assert_eq!(summarize(k_sums(&report, 3, 100, None)), Summary::default());
+    }
+}
@@ -1,56 +1,95 @@
+mod input;
+mod ksum;
+mod report;
+
 use std::fs::File;
-use std::collections::HashSet;
 use std::str::FromStr;
 use std::io::{self, BufRead};
-use std::iter::FromIterator;
 
-fn read_data(filename: &str) -> Vec<i32> {
-    let mut data = Vec::new();
+use input::{read_entries, ParseError};
+use ksum::{find_k_sum, k_sums, product, summarize};
+use report::{ExpenseReport, Operand};
+
+/// Command line options, everything is optional:
+/// `[--input FILE] [-k N] [--target N] [--all] [--limit N] [--summary]`, `--input -` reads stdin.
+#[derive(Debug, Default)]
+struct Options {
+    input: String,
+    k: Option<usize>,
+    target: i64,
+    all: bool,
+    limit: Option<usize>,
+    summary: bool,
+}
 
-    let file = File::open(filename);
-    let reader = file.map(|e| io::BufReader::new(e).lines());
-    if let Ok(lines) = reader {
-        for line in lines {
-            if let Ok(e) = line {
-                data.push(i32::from_str(&e).unwrap());
+impl Options {
+    fn from_args(mut args: impl Iterator<Item=String>) -> Self {
+        let mut options = Options { input: "./data.txt".to_owned(), target: 2020, ..Default::default() };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--input" => options.input = args.next().unwrap(),
+                "-k" => options.k = args.next().map(|e| usize::from_str(&e).unwrap()),
+                "--target" => options.target = args.next().map(|e| i64::from_str(&e).unwrap()).unwrap(),
+                "--all" => options.all = true,
+                "--limit" => options.limit = args.next().map(|e| usize::from_str(&e).unwrap()),
+                "--summary" => options.summary = true,
+                _ => panic!("Unknown argument: {}", arg),
             }
         }
+        options
     }
-
-    data
 }
 
-fn construct_set(data: &[i32]) -> HashSet<i32> {
-    return HashSet::from_iter(data.into_iter().cloned())
+fn read_report(input: &str) -> Result<ExpenseReport, ParseError> {
+    let reader: Box<dyn BufRead> = if input == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(io::BufReader::new(File::open(input)?))
+    };
+
+    read_entries(reader).collect()
 }
 
-fn find_double(set: &HashSet<i32>) {
-    for i in set {
-        if let Some(result) = set.get(&(&2020 - i)) {
-            println!("{} * {} = {}", i, result, i * result);
-            return;
-        }
-    }
+fn print_operands(operands: &[Operand]) {
+    let terms: Vec<String> = operands.iter().map(|e| format!("{} (line {})", e.value, e.line)).collect();
+    println!("{} = {}", terms.join(" * "), product(operands));
 }
 
-fn find_triple_brute(set: &HashSet<i32>) {
-    for i in set {
-        for j in set {
-            if i == j { continue; }
-            if let Some(result) = set.get(&(&2020 - i - j)) {
-                println!("{} * {} * {} = {}", i, j, result, i * j * result);
-                return;
-            }
+fn print_k_sum(report: &ExpenseReport, k: usize, options: &Options) {
+    if options.summary {
+        let summary = summarize(k_sums(report, k, options.target, options.limit));
+        match (summary.min_product, summary.max_product) {
+            (Some(min), Some(max)) =>
+                println!("{} solutions for k = {}, products between {} and {}", summary.count, k, min, max),
+            _ => println!("No {} entries sum to {}", k, options.target),
+        }
+    } else if options.all {
+        for operands in k_sums(report, k, options.target, options.limit) {
+            print_operands(&operands);
+        }
+    } else {
+        match find_k_sum(report, k, options.target) {
+            Some(operands) => print_operands(&operands),
+            None => println!("No {} entries sum to {}", k, options.target),
         }
     }
 }
 
 fn main() {
-    let data = read_data("./data.txt");
-    let set = construct_set(&data);
-
-    find_double(&set);
+    let options = Options::from_args(std::env::args().skip(1));
+    let report = match read_report(&options.input) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{}: {}", options.input, e);
+            std::process::exit(1);
+        }
+    };
 
-    // TODO: We can probably do better than this, which is O(n^2)
-    find_triple_brute(&set);
+    match options.k {
+        Some(k) => print_k_sum(&report, k, &options),
+        None => {
+            print_k_sum(&report, 2, &options);
+            print_k_sum(&report, 3, &options);
+        }
+    }
 }
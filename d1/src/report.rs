@@ -0,0 +1,57 @@
+use std::collections::BTreeMap;
+use std::iter::FromIterator;
+
+/// A value from the expense report together with the (1-based) line it was read from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Operand {
+    pub value: i64,
+    pub line: usize,
+}
+
+/// Multiset of expense report entries. Every distinct value keeps the lines it appeared on, so the
+/// number of lines is also the number of times the value can be used.
+#[derive(Debug, Default)]
+pub struct ExpenseReport {
+    entries: BTreeMap<i64, Vec<usize>>,
+}
+
+impl ExpenseReport {
+    pub fn insert(&mut self, value: i64, line: usize) {
+        self.entries.entry(value).or_default().push(line);
+    }
+
+    /// All entries sorted by value and then by line, with each value repeated at most `max_copies`
+    /// times. A k-sum never needs more than k copies of the same value.
+    pub fn operands(&self, max_copies: usize) -> Vec<Operand> {
+        self.entries.iter()
+            .flat_map(|(&value, lines)| lines.iter().take(max_copies).map(move |&line| Operand { value, line }))
+            .collect()
+    }
+}
+
+/// Builds the report from `(line, value)` pairs.
+impl FromIterator<(usize, i64)> for ExpenseReport {
+    fn from_iter<T: IntoIterator<Item=(usize, i64)>>(iter: T) -> Self {
+        let mut report = ExpenseReport::default();
+        for (line, value) in iter {
+            report.insert(value, line);
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_keeps_duplicates() {
+        let report: ExpenseReport = vec![(1, 1010), (2, 5), (3, 1010), (4, 1010)].into_iter().collect();
+        assert_eq!(report.operands(usize::MAX).len(), 4);
+
+        let operands = report.operands(2);
+        assert_eq!(operands, vec![Operand { value: 5, line: 2 },
+                                  Operand { value: 1010, line: 1 },
+                                  Operand { value: 1010, line: 3 }]);
+    }
+}
@@ -1,66 +1,147 @@
+mod policy;
+mod report;
+
 use std::fs::File;
 use std::io;
 use std::io::BufRead;
 use std::str::FromStr;
 
+use unicode_segmentation::UnicodeSegmentation;
+
+use policy::Policy;
+use report::{Format, ReportWriter};
+
 fn read_file(file_name: &str) -> Vec<String> {
     let mut data = Vec::new();
     let file = File::open(file_name);
     if let Ok(f) = file {
         let reader = io::BufReader::new(f);
-        for line_result in reader.lines() {
-            if let Ok(line) = line_result {
-                data.push(line);
-            }
+        for line in reader.lines().map_while(Result::ok) {
+            data.push(line);
         }
     }
 
     data
 }
 
+/// What counts as a single character of a password.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Segmentation {
+    /// Unicode scalar values, i.e. Rust `char`s.
+    Chars,
+    /// Extended grapheme clusters, so that e.g. `e` followed by a combining accent is one character.
+    Graphemes,
+}
+
+impl Segmentation {
+    fn split(self, s: &str) -> Vec<&str> {
+        match self {
+            Segmentation::Chars => s.char_indices().map(|(i, ch)| &s[i..i + ch.len_utf8()]).collect(),
+            Segmentation::Graphemes => s.graphemes(true).collect(),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Entry<'a> {
     i1: usize,
     i2: usize,
     ch: &'a str,
     password: &'a str,
+    /// The password split into characters according to the segmentation it was parsed with.
+    symbols: Vec<&'a str>,
 }
 
-fn split_line(line: &str) -> Entry {
-    let splitted: Vec<&str> = line.split([' ', '-', ':'].as_ref())
-        .filter(|e| !e.is_empty())
-        .collect();
+/// Parses lines of the form `1-3 a: abcde`, describing what is wrong otherwise.
+fn parse_line(line: &str, segmentation: Segmentation) -> Result<Entry<'_>, String> {
+    let (rule, password) = line.split_once(": ").ok_or("missing ': ' before password")?;
+    let (range, ch) = rule.split_once(' ').ok_or("missing space before policy character")?;
+    let (i1, i2) = range.split_once('-').ok_or("missing '-' in range")?;
 
-    Entry {
-        i1: usize::from_str(splitted[0]).unwrap(),
-        i2: usize::from_str(splitted[1]).unwrap(),
-        ch: splitted[2],
-        password: splitted[3]
+    let parse_number = |e: &str| usize::from_str(e).map_err(|_| format!("invalid number {:?}", e));
+    if segmentation.split(ch).len() != 1 {
+        return Err(format!("policy character {:?} is not a single character", ch));
     }
+
+    Ok(Entry {
+        i1: parse_number(i1)?,
+        i2: parse_number(i2)?,
+        ch,
+        password,
+        symbols: segmentation.split(password),
+    })
+}
+
+struct Options {
+    policies: Vec<Box<dyn Policy>>,
+    segmentation: Segmentation,
+    report: Option<Format>,
 }
 
-fn parse_line_one(line: &str) -> bool {
-    let entry = split_line(line);
-    let count = entry.password.split("").filter(|e| e == &entry.ch).count();
+/// Policies are given by name on the command line, the default is both parts of the puzzle.
+/// `--graphemes` switches from Unicode scalar values to grapheme clusters, and
+/// `--report text|json|csv` prints a record for every line instead of the counts.
+fn parse_args(mut args: impl Iterator<Item=String>) -> Options {
+    let mut segmentation = Segmentation::Chars;
+    let mut report = None;
+    let mut names = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--graphemes" => segmentation = Segmentation::Graphemes,
+            "--report" => {
+                let format = args.next().unwrap_or_default();
+                report = Some(Format::from_name(&format).unwrap_or_else(|| panic!("Unknown report format: {}", format)));
+            }
+            _ => names.push(arg),
+        }
+    }
+
+    if names.is_empty() {
+        names = vec!["count".to_owned(), "positions".to_owned()];
+    }
+
+    let policies = names.iter()
+        .map(|e| policy::from_name(e).unwrap_or_else(|| panic!("Unknown policy: {}", e)))
+        .collect();
 
-    count <= entry.i2 && count >= entry.i1
+    Options { policies, segmentation, report }
 }
 
-fn parse_line_two(line: &str) -> bool {
-    let entry = split_line(line);
+fn print_report(data: &[String], options: &Options, format: Format) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut writer = ReportWriter::new(stdout.lock(), format, &options.policies)?;
+    for (i, line) in data.iter().enumerate() {
+        writer.write(i + 1, &parse_line(line, options.segmentation))?;
+    }
+    Ok(())
+}
+
+fn print_counts(data: &[String], options: &Options) {
+    let mut counts = vec![0; options.policies.len()];
+    for (i, line) in data.iter().enumerate() {
+        match parse_line(line, options.segmentation) {
+            Ok(entry) => {
+                for (policy, count) in options.policies.iter().zip(counts.iter_mut()) {
+                    if policy.is_valid(&entry) { *count += 1; }
+                }
+            }
+            Err(error) => eprintln!("Skipping line {}: {}", i + 1, error),
+        }
+    }
 
-    let password_chars: Vec<&str> = entry.password.split("").filter(|e| !e.is_empty()).collect();
-    (password_chars[entry.i1 - 1] == entry.ch) ^ (password_chars[entry.i2 - 1] == entry.ch)
+    for (policy, count) in options.policies.iter().zip(counts) {
+        println!("Count for {}: {}", policy.name(), count);
+    }
 }
 
 fn main() {
+    let options = parse_args(std::env::args().skip(1));
     let data = read_file("input");
 
-    let count1 = data.iter().map(|e| parse_line_one(&e)).filter(|&e| e).count();
-    println!("Count for part 1: {}", count1);
-
-    let count2 = data.iter().map(|e| parse_line_two(&e)).filter(|&e| e).count();
-    println!("Count for part 2: {}", count2);
+    match options.report {
+        Some(format) => print_report(&data, &options, format).unwrap(),
+        None => print_counts(&data, &options),
+    }
 }
 
 #[cfg(test)]
@@ -68,8 +149,8 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_split_line() {
-        let splitted = split_line("4-8 g: ggtxgtgbg");
+    fn test_parse_line() {
+        let splitted = parse_line("4-8 g: ggtxgtgbg", Segmentation::Chars).unwrap();
         assert_eq!(splitted.i1, 4);
         assert_eq!(splitted.i2, 8);
         assert_eq!(splitted.ch, "g");
@@ -77,18 +158,37 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_line_one() {
-        assert_eq!(parse_line_one("4-8 g: ggtxgtgbg"), true);
-        assert_eq!(parse_line_one("4-8 g: abcde"), false);
+    fn test_parse_line_errors() {
+        let error = |line| parse_line(line, Segmentation::Chars).unwrap_err();
+        assert_eq!(error("4-8 g ggtxgtgbg"), "missing ': ' before password");
+        assert_eq!(error("4-8: ggtxgtgbg"), "missing space before policy character");
+        assert_eq!(error("4 g: ggtxgtgbg"), "missing '-' in range");
+        assert_eq!(error("4-x g: ggtxgtgbg"), "invalid number \"x\"");
+        assert_eq!(error("4-8 gg: ggtxgtgbg"), "policy character \"gg\" is not a single character");
+        assert_eq!(error(""), "missing ': ' before password");
     }
 
     #[test]
-    fn test_parse_line_two() {
-        assert_eq!(parse_line_two("4-8 g: ggtxgtgbg"), false);
-        assert_eq!(parse_line_two("1-3 a: abcde"), true);
-        assert_eq!(parse_line_two("1-3 b: cdefg"), false);
-        assert_eq!(parse_line_two("2-9 c: ccccccccc"), false);
+    fn test_segmentation() {
+        assert_eq!(Segmentation::Chars.split("abc"), vec!["a", "b", "c"]);
+        assert_eq!(Segmentation::Chars.split("çé"), vec!["ç", "é"]);
+        assert_eq!(Segmentation::Chars.split("e\u{301}x"), vec!["e", "\u{301}", "x"]);
+        assert_eq!(Segmentation::Graphemes.split("e\u{301}x"), vec!["e\u{301}", "x"]);
+        assert_eq!(Segmentation::Graphemes.split("👍🏽a"), vec!["👍🏽", "a"]);
+    }
 
+    #[test]
+    fn test_parse_line_unicode() {
+        let entry = parse_line("1-2 é: éaé", Segmentation::Chars).unwrap();
+        assert_eq!(entry.ch, "é");
+        assert_eq!(entry.symbols, vec!["é", "a", "é"]);
+
+        let entry = parse_line("1-2 👍🏽: a👍🏽", Segmentation::Graphemes).unwrap();
+        assert_eq!(entry.symbols, vec!["a", "👍🏽"]);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_parse_line_multiple_chars() {
+        assert!(parse_line("1-2 👍🏽: a👍🏽", Segmentation::Chars).is_err());
+    }
+}
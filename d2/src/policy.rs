@@ -0,0 +1,217 @@
+use std::str::FromStr;
+
+use crate::Entry;
+
+/// A rule that decides whether the password of an entry is valid. `i1`, `i2` and `ch` of the
+/// entry are interpreted by each policy in its own way. Positions are counted in characters of the
+/// entry's segmentation, and a position past the end of the password makes it invalid.
+pub trait Policy {
+    fn name(&self) -> String;
+
+    /// Returns a short machine-readable reason such as `count 9 above max 8` when invalid.
+    fn check(&self, entry: &Entry) -> Result<(), String>;
+
+    fn is_valid(&self, entry: &Entry) -> bool {
+        self.check(entry).is_ok()
+    }
+}
+
+/// `ch` occurs at least `i1` and at most `i2` times (part 1).
+pub struct CountInRange;
+
+/// `ch` is at exactly one of the 1-based positions `i1` and `i2` (part 2).
+pub struct ExactlyOneOfPositions;
+
+/// `ch` is at `min` or more of the 1-based positions `i1` and `i2`.
+pub struct AtLeastOfPositions {
+    pub min: usize,
+}
+
+/// `ch` does not occur in the password at all.
+pub struct ForbiddenCharacter;
+
+/// No character is repeated more than `max` times in a row.
+pub struct MaxConsecutiveRepeats {
+    pub max: usize,
+}
+
+/// Whether `ch` is at the 1-based `position`.
+fn matches_at(entry: &Entry, position: usize) -> Result<bool, String> {
+    position.checked_sub(1)
+        .and_then(|i| entry.symbols.get(i))
+        .map(|&e| e == entry.ch)
+        .ok_or_else(|| format!("position {} out of range 1..={}", position, entry.symbols.len()))
+}
+
+impl Policy for CountInRange {
+    fn name(&self) -> String { "count".to_owned() }
+
+    fn check(&self, entry: &Entry) -> Result<(), String> {
+        let count = entry.symbols.iter().filter(|&&e| e == entry.ch).count();
+        if count < entry.i1 {
+            Err(format!("count {} below min {}", count, entry.i1))
+        } else if count > entry.i2 {
+            Err(format!("count {} above max {}", count, entry.i2))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Policy for ExactlyOneOfPositions {
+    fn name(&self) -> String { "positions".to_owned() }
+
+    fn check(&self, entry: &Entry) -> Result<(), String> {
+        match (matches_at(entry, entry.i1)?, matches_at(entry, entry.i2)?) {
+            (true, true) => Err("both positions match".to_owned()),
+            (false, false) => Err("no position matches".to_owned()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Policy for AtLeastOfPositions {
+    fn name(&self) -> String { format!("at-least:{}", self.min) }
+
+    fn check(&self, entry: &Entry) -> Result<(), String> {
+        let count = (matches_at(entry, entry.i1)? as usize) + (matches_at(entry, entry.i2)? as usize);
+        if count >= self.min {
+            Ok(())
+        } else {
+            Err(format!("{} positions match, need {}", count, self.min))
+        }
+    }
+}
+
+impl Policy for ForbiddenCharacter {
+    fn name(&self) -> String { "forbidden".to_owned() }
+
+    fn check(&self, entry: &Entry) -> Result<(), String> {
+        match entry.symbols.iter().position(|&e| e == entry.ch) {
+            Some(i) => Err(format!("forbidden character at position {}", i + 1)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Policy for MaxConsecutiveRepeats {
+    fn name(&self) -> String { format!("max-repeats:{}", self.max) }
+
+    fn check(&self, entry: &Entry) -> Result<(), String> {
+        let symbols = &entry.symbols;
+        let mut run = 0;
+        for (i, symbol) in symbols.iter().enumerate() {
+            run = if i > 0 && symbols[i - 1] == *symbol { run + 1 } else { 1 };
+            if run > self.max {
+                return Err(format!("run of {} above max {} at position {}", run, self.max, i + 2 - run));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Creates a policy from its command line name, e.g. `count`, `positions`, `at-least:2`,
+/// `forbidden` or `max-repeats:3`.
+pub fn from_name(name: &str) -> Option<Box<dyn Policy>> {
+    let mut splitted = name.splitn(2, ':');
+    let kind = splitted.next()?;
+    let arg = splitted.next().map(usize::from_str);
+
+    match (kind, arg) {
+        ("count", None) => Some(Box::new(CountInRange)),
+        ("positions", None) => Some(Box::new(ExactlyOneOfPositions)),
+        ("at-least", Some(Ok(min))) => Some(Box::new(AtLeastOfPositions { min })),
+        ("forbidden", None) => Some(Box::new(ForbiddenCharacter)),
+        ("max-repeats", Some(Ok(max))) => Some(Box::new(MaxConsecutiveRepeats { max })),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_line, Segmentation};
+
+    fn check(policy: &dyn Policy, line: &str) -> bool {
+        policy.is_valid(&parse_line(line, Segmentation::Chars).unwrap())
+    }
+
+    fn check_graphemes(policy: &dyn Policy, line: &str) -> bool {
+        policy.is_valid(&parse_line(line, Segmentation::Graphemes).unwrap())
+    }
+
+    fn reason(policy: &dyn Policy, line: &str) -> String {
+        policy.check(&parse_line(line, Segmentation::Chars).unwrap()).unwrap_err()
+    }
+
+    #[test]
+    fn test_count_in_range() {
+        assert!(check(&CountInRange, "4-8 g: ggtxgtgbg"));
+        assert!(!check(&CountInRange, "4-8 g: abcde"));
+    }
+
+    #[test]
+    fn test_exactly_one_of_positions() {
+        assert!(!check(&ExactlyOneOfPositions, "4-8 g: ggtxgtgbg"));
+        assert!(check(&ExactlyOneOfPositions, "1-3 a: abcde"));
+        assert!(!check(&ExactlyOneOfPositions, "1-3 b: cdefg"));
+        assert!(!check(&ExactlyOneOfPositions, "2-9 c: ccccccccc"));
+        assert!(!check(&ExactlyOneOfPositions, "1-30 a: abcde"));
+        assert!(!check(&ExactlyOneOfPositions, "0-1 a: abcde"));
+    }
+
+    #[test]
+    fn test_unicode() {
+        assert!(check(&CountInRange, "2-2 é: éaé"));
+        assert!(check(&ExactlyOneOfPositions, "1-3 ß: ßaa"));
+        assert!(check(&ExactlyOneOfPositions, "2-3 🎄: a🎄b"));
+        assert!(check_graphemes(&CountInRange, "2-2 e\u{301}: e\u{301}ee\u{301}"));
+        assert!(check_graphemes(&ExactlyOneOfPositions, "2-3 👍🏽: a👍🏽👍"));
+        assert!(check_graphemes(&MaxConsecutiveRepeats { max: 1 }, "1-1 a: e\u{301}e"));
+        assert!(!check(&MaxConsecutiveRepeats { max: 1 }, "1-1 a: e\u{301}\u{301}"));
+    }
+
+    #[test]
+    fn test_at_least_of_positions() {
+        assert!(check(&AtLeastOfPositions { min: 2 }, "2-9 c: ccccccccc"));
+        assert!(!check(&AtLeastOfPositions { min: 2 }, "1-3 a: abcde"));
+        assert!(check(&AtLeastOfPositions { min: 1 }, "1-3 a: abcde"));
+        assert!(!check(&AtLeastOfPositions { min: 1 }, "1-3 b: cdefg"));
+        assert!(!check(&AtLeastOfPositions { min: 1 }, "1-9 a: abcde"));
+    }
+
+    #[test]
+    fn test_forbidden_character() {
+        assert!(check(&ForbiddenCharacter, "1-3 b: cdefg"));
+        assert!(!check(&ForbiddenCharacter, "1-3 a: abcde"));
+    }
+
+    #[test]
+    fn test_max_consecutive_repeats() {
+        assert!(check(&MaxConsecutiveRepeats { max: 2 }, "1-3 a: aabaa"));
+        assert!(!check(&MaxConsecutiveRepeats { max: 2 }, "1-3 a: abbba"));
+        assert!(check(&MaxConsecutiveRepeats { max: 3 }, "1-3 a: abbba"));
+    }
+
+    #[test]
+    fn test_reasons() {
+        assert_eq!(reason(&CountInRange, "1-8 g: ggggggggg"), "count 9 above max 8");
+        assert_eq!(reason(&CountInRange, "4-8 g: abcde"), "count 0 below min 4");
+        assert_eq!(reason(&ExactlyOneOfPositions, "2-9 c: ccccccccc"), "both positions match");
+        assert_eq!(reason(&ExactlyOneOfPositions, "1-3 b: cdefg"), "no position matches");
+        assert_eq!(reason(&ExactlyOneOfPositions, "1-30 a: abcde"), "position 30 out of range 1..=5");
+        assert_eq!(reason(&AtLeastOfPositions { min: 2 }, "1-3 a: abcde"), "1 positions match, need 2");
+        assert_eq!(reason(&ForbiddenCharacter, "1-3 c: abcde"), "forbidden character at position 3");
+        assert_eq!(reason(&MaxConsecutiveRepeats { max: 2 }, "1-3 a: abbba"), "run of 3 above max 2 at position 2");
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(from_name("count").unwrap().name(), "count");
+        assert_eq!(from_name("at-least:2").unwrap().name(), "at-least:2");
+        assert_eq!(from_name("max-repeats:3").unwrap().name(), "max-repeats:3");
+        assert!(from_name("at-least").is_none());
+        assert!(from_name("count:2").is_none());
+        assert!(from_name("unknown").is_none());
+    }
+}
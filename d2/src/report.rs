@@ -0,0 +1,173 @@
+use std::io::{self, Write};
+
+use crate::policy::Policy;
+use crate::Entry;
+
+/// Output formats of the per-line validation report.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    JsonLines,
+    Csv,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" | "jsonl" => Some(Format::JsonLines),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+/// Writes one record per input line, either the parsed entry with the result of every policy or
+/// the parse error of the line.
+pub struct ReportWriter<'a, W: Write> {
+    out: W,
+    format: Format,
+    policies: &'a [Box<dyn Policy>],
+}
+
+impl<'a, W: Write> ReportWriter<'a, W> {
+    /// Creates the writer and outputs the header if the format has one.
+    pub fn new(mut out: W, format: Format, policies: &'a [Box<dyn Policy>]) -> io::Result<Self> {
+        if format == Format::Csv {
+            let mut columns: Vec<String> = ["line", "i1", "i2", "ch", "password", "error"].iter()
+                .map(|&e| e.to_owned())
+                .collect();
+            for policy in policies {
+                columns.push(csv_field(&policy.name()));
+                columns.push(csv_field(&format!("{} reason", policy.name())));
+            }
+            writeln!(out, "{}", columns.join(","))?;
+        }
+
+        Ok(ReportWriter { out, format, policies })
+    }
+
+    pub fn write(&mut self, line: usize, entry: &Result<Entry, String>) -> io::Result<()> {
+        match (self.format, entry) {
+            (Format::Text, Ok(entry)) => {
+                write!(self.out, "line {}: {}-{} {:?}: {:?}", line, entry.i1, entry.i2, entry.ch, entry.password)?;
+                for policy in self.policies {
+                    match policy.check(entry) {
+                        Ok(()) => write!(self.out, " | {}: valid", policy.name())?,
+                        Err(reason) => write!(self.out, " | {}: invalid, {}", policy.name(), reason)?,
+                    }
+                }
+                writeln!(self.out)
+            }
+            (Format::Text, Err(error)) => writeln!(self.out, "line {}: parse error, {}", line, error),
+            (Format::JsonLines, Ok(entry)) => {
+                let results: Vec<String> = self.policies.iter()
+                    .map(|policy| match policy.check(entry) {
+                        Ok(()) => format!("{{\"policy\":{},\"valid\":true}}", json_string(&policy.name())),
+                        Err(reason) => format!("{{\"policy\":{},\"valid\":false,\"reason\":{}}}",
+                                               json_string(&policy.name()), json_string(&reason)),
+                    })
+                    .collect();
+                writeln!(self.out, "{{\"line\":{},\"i1\":{},\"i2\":{},\"ch\":{},\"password\":{},\"results\":[{}]}}",
+                         line, entry.i1, entry.i2, json_string(entry.ch), json_string(entry.password),
+                         results.join(","))
+            }
+            (Format::JsonLines, Err(error)) =>
+                writeln!(self.out, "{{\"line\":{},\"error\":{}}}", line, json_string(error)),
+            (Format::Csv, Ok(entry)) => {
+                let mut fields = vec![line.to_string(), entry.i1.to_string(), entry.i2.to_string(),
+                                      csv_field(entry.ch), csv_field(entry.password), String::new()];
+                for policy in self.policies {
+                    let result = policy.check(entry);
+                    fields.push(result.is_ok().to_string());
+                    fields.push(csv_field(&result.err().unwrap_or_default()));
+                }
+                writeln!(self.out, "{}", fields.join(","))
+            }
+            (Format::Csv, Err(error)) => {
+                let mut fields = vec![line.to_string(), String::new(), String::new(), String::new(),
+                                      String::new(), csv_field(error)];
+                fields.resize(fields.len() + 2 * self.policies.len(), String::new());
+                writeln!(self.out, "{}", fields.join(","))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{CountInRange, ExactlyOneOfPositions};
+    use crate::{parse_line, Segmentation};
+
+    fn report(format: Format, lines: &[&str]) -> String {
+        let policies: Vec<Box<dyn Policy>> = vec![Box::new(CountInRange), Box::new(ExactlyOneOfPositions)];
+        let mut out = Vec::new();
+        let mut writer = ReportWriter::new(&mut out, format, &policies).unwrap();
+        for (i, line) in lines.iter().enumerate() {
+            writer.write(i + 1, &parse_line(line, Segmentation::Chars)).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    const LINES: [&str; 3] = ["1-3 a: abcde", "2-9 c: ccccccccc", "1-3 b cdefg"];
+
+    #[test]
+    fn test_text() {
+        assert_eq!(report(Format::Text, &LINES),
+                   "line 1: 1-3 \"a\": \"abcde\" | count: valid | positions: valid\n\
+                    line 2: 2-9 \"c\": \"ccccccccc\" | count: valid | positions: invalid, both positions match\n\
+                    line 3: parse error, missing ': ' before password\n");
+    }
+
+    #[test]
+    fn test_json_lines() {
+        assert_eq!(report(Format::JsonLines, &LINES[1..]),
+                   "{\"line\":1,\"i1\":2,\"i2\":9,\"ch\":\"c\",\"password\":\"ccccccccc\",\"results\":[\
+                    {\"policy\":\"count\",\"valid\":true},\
+                    {\"policy\":\"positions\",\"valid\":false,\"reason\":\"both positions match\"}]}\n\
+                    {\"line\":2,\"error\":\"missing ': ' before password\"}\n");
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(report(Format::Csv, &LINES[1..]),
+                   "line,i1,i2,ch,password,error,count,count reason,positions,positions reason\n\
+                    1,2,9,c,ccccccccc,,true,,false,both positions match\n\
+                    2,,,,,missing ': ' before password,,,,\n");
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(json_string("a\"b\\c\n\u{1}"), "\"a\\\"b\\\\c\\n\\u0001\"");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("a\"b"), "\"a\"\"b\"");
+        assert_eq!(csv_field("ab"), "ab");
+    }
+}
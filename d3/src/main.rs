@@ -1,61 +1,122 @@
+mod render;
+mod search;
+mod slope;
+
 use std::fs::File;
 use std::io;
 use std::io::BufRead;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use render::{render, Style};
+use search::{best_slopes, Objective};
+use slope::{count_trees, Edge, Slope};
 
 fn read_lines(filename: &str) -> Vec<Vec<char>> {
     let file = File::open(filename);
     let reader = file.map(|e| io::BufReader::new(e).lines());
     let mut result = Vec::new();
     if let Ok(lines) = reader {
-        for line in lines {
-            if let Ok(l) = line {
-                let line_vec = l.chars().collect();
-                result.push(line_vec);
-            }
+        for l in lines.map_while(Result::ok) {
+            let line_vec = l.chars().collect();
+            result.push(line_vec);
         }
     }
 
     result
 }
 
-fn count_trees(data: &Vec<Vec<char>>, x_vel: usize, y_vel: usize) -> usize {
-    let size_y = data.len();
-    let size_x = data.get(0).unwrap().len();
+/// Search for the best slopes within `dx` and `dy` bounds.
+struct Search {
+    objective: Objective,
+    dx: RangeInclusive<i64>,
+    dy: RangeInclusive<i64>,
+    top: usize,
+}
 
-    let (mut cur_x, mut cur_y) = (0, 0);
-    let mut num_trees = 0;
+struct Options {
+    slopes: Vec<Slope>,
+    edge: Edge,
+    search: Option<Search>,
+    render: Option<Style>,
+}
 
-    loop {
-        cur_x = (cur_x + x_vel) % size_x;
-        cur_y += y_vel;
+/// Parses ranges like `-3..=7`.
+fn parse_range(s: &str) -> RangeInclusive<i64> {
+    let (start, end) = s.split_once("..=").unwrap_or_else(|| panic!("Range {:?} is not of the form a..=b", s));
+    i64::from_str(start).unwrap()..=i64::from_str(end).unwrap()
+}
 
-        if cur_y >= size_y { break; }
+/// Arguments are slopes such as `3/1` or `-1/2`, optionally with `--edge wrap|clamp|stop`.
+/// `--search min|max` looks for the best slopes instead, bounded by `--dx a..=b` and `--dy a..=b`,
+/// and `--top n` of them are printed. `--render plain|ansi` draws the path of the first slope.
+fn parse_args(mut args: impl Iterator<Item=String>) -> Options {
+    let mut options = Options { slopes: Vec::new(), edge: Edge::Wrap, search: None, render: None };
+    let mut search = Search { objective: Objective::Min, dx: 0..=10, dy: 1..=3, top: 5 };
+    let mut is_search = false;
 
-        if data[cur_y][cur_x] == '#' {
-            num_trees += 1;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_default();
+        match arg.as_str() {
+            "--edge" => options.edge = Edge::from_str(&value()).unwrap(),
+            "--search" => {
+                is_search = true;
+                search.objective = match value().as_str() {
+                    "min" => Objective::Min,
+                    "max" => Objective::Max,
+                    other => panic!("Unknown objective: {}", other),
+                };
+            }
+            "--dx" => search.dx = parse_range(&value()),
+            "--dy" => search.dy = parse_range(&value()),
+            "--top" => search.top = usize::from_str(&value()).unwrap(),
+            "--render" => {
+                options.render = match value().as_str() {
+                    "plain" => Some(Style::Plain),
+                    "ansi" => Some(Style::Ansi),
+                    other => panic!("Unknown render style: {}", other),
+                };
+            }
+            _ => options.slopes.push(Slope::from_str(&arg).unwrap()),
         }
     }
 
-    num_trees
+    if is_search { options.search = Some(search); }
+    options
 }
 
 fn main() {
     let data = read_lines("input");
-    let num_trees_one = count_trees(&data, 3, 1);
-    println!("Trees for part 1: {}", num_trees_one);
+    let Options { slopes, edge, search, render: style } = parse_args(std::env::args().skip(1));
 
-    let params: Vec<(usize, usize)> = vec![(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
-    let num_trees_two = params.into_iter()
-        .map( |(x, y)| count_trees(&data, x, y) )
-        .fold(1, |acc, e| acc * e);
+    if let Some(style) = style {
+        let slope = slopes.first().cloned().unwrap_or(Slope { dx: 3, dy: 1 });
+        print!("{}", render(&data, slope, edge, style));
+        return;
+    }
 
-    println!("Trees for part 2: {}", num_trees_two);
-}
+    if let Some(search) = search {
+        let results = best_slopes(&data, search.dx, search.dy, edge, search.objective, search.top);
+        for (slope, count) in results {
+            println!("Trees for {}/{}: {}", slope.dx, slope.dy, count);
+        }
+        return;
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    if !slopes.is_empty() {
+        for slope in slopes {
+            println!("Trees for {}/{}: {}", slope.dx, slope.dy, count_trees(&data, slope, edge));
+        }
+        return;
+    }
+
+    let num_trees_one = count_trees(&data, Slope { dx: 3, dy: 1 }, edge);
+    println!("Trees for part 1: {}", num_trees_one);
 
-//    #[test]
+    let params: Vec<(i64, i64)> = vec![(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+    let num_trees_two: usize = params.into_iter()
+        .map(|(dx, dy)| count_trees(&data, Slope { dx, dy }, edge))
+        .product();
 
+    println!("Trees for part 2: {}", num_trees_two);
 }
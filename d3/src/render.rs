@@ -0,0 +1,88 @@
+use crate::slope::{path, Edge, Slope};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    Plain,
+    /// Hit trees in red and clear cells in green, using ANSI escape codes.
+    Ansi,
+}
+
+/// Draws the map with the path on it, `O` for trees that were hit and `X` for clear cells. A
+/// wrapping map is repeated to the left and right as many times as the path needs.
+pub fn render(data: &[Vec<char>], slope: Slope, edge: Edge, style: Style) -> String {
+    let size_x = data.first().map_or(0, |row| row.len()) as i64;
+    if size_x == 0 { return String::new(); }
+
+    // The path reports wrapped columns, the unwrapped one is the number of steps times dx.
+    let visited: Vec<(i64, usize)> = path(data, slope, edge)
+        .zip(1..)
+        .map(|((x, y), step)| if edge == Edge::Wrap { (step * slope.dx, y) } else { (x as i64, y) })
+        .collect();
+
+    let min_x = visited.iter().map(|e| e.0).min().unwrap_or(0).min(0);
+    let max_x = visited.iter().map(|e| e.0).max().unwrap_or(0).max(size_x - 1);
+    let first_column = min_x.div_euclid(size_x) * size_x;
+    let last_column = (max_x.div_euclid(size_x) + 1) * size_x;
+
+    let mut grid: Vec<Vec<char>> = data.iter()
+        .map(|row| (first_column..last_column).map(|x| row[x.rem_euclid(size_x) as usize]).collect())
+        .collect();
+
+    for &(x, y) in &visited {
+        let cell = &mut grid[y][(x - first_column) as usize];
+        *cell = if *cell == '#' { 'O' } else { 'X' };
+    }
+
+    let mut result = String::new();
+    for row in grid {
+        for ch in row {
+            match (style, ch) {
+                (Style::Ansi, 'O') => result.push_str("\x1b[1;31mO\x1b[0m"),
+                (Style::Ansi, 'X') => result.push_str("\x1b[1;32mX\x1b[0m"),
+                _ => result.push(ch),
+            }
+        }
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slope::tests::example;
+
+    #[test]
+    fn test_render_example() {
+        let expected = "..##.........##.........##.......
+#..X#...#..#...#...#..#...#...#..
+.#....O..#..#....#..#..#....#..#.
+..#.#...#X#..#.#...#.#..#.#...#.#
+.#...##..#..O...##..#..#...##..#.
+..#.##.......#.O#.......#.##.....
+.#.#.#....#.#.#.#.X..#.#.#.#....#
+.#........#.#........O.#........#
+#.##...#...#.##...#...#.O#...#...
+#...##....##...##....##...#O....#
+.#..#...#.#.#..#...#.#.#..#...O.#
+";
+        assert_eq!(render(&example(), Slope { dx: 3, dy: 1 }, Edge::Wrap, Style::Plain), expected);
+    }
+
+    #[test]
+    fn test_render_leftward() {
+        let data: Vec<Vec<char>> = vec!["..#".chars().collect(), ".#.".chars().collect(), "#..".chars().collect()];
+        let rendered = render(&data, Slope { dx: -2, dy: 1 }, Edge::Wrap, Style::Plain);
+        assert_eq!(rendered, "..#..#..#\n.#..O..#.\n#.X#..#..\n");
+
+        let rendered = render(&data, Slope { dx: -2, dy: 1 }, Edge::Clamp, Style::Plain);
+        assert_eq!(rendered, "..#\nX#.\nO..\n");
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let data: Vec<Vec<char>> = vec![".".chars().collect(), "#".chars().collect(), ".".chars().collect()];
+        let rendered = render(&data, Slope { dx: 0, dy: 1 }, Edge::Wrap, Style::Ansi);
+        assert_eq!(rendered, ".\n\x1b[1;31mO\x1b[0m\n\x1b[1;32mX\x1b[0m\n");
+    }
+}
@@ -0,0 +1,129 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use crate::slope::{Edge, Path, Slope};
+
+/// Tree positions packed into a bitset per row, so that counting a slope only tests the bits it visits.
+pub struct Occupancy {
+    size_x: usize,
+    rows: Vec<Vec<u64>>,
+}
+
+impl Occupancy {
+    pub fn new(data: &[Vec<char>]) -> Self {
+        let size_x = data.first().map_or(0, |row| row.len());
+        let rows = data.iter()
+            .map(|row| {
+                let mut bits = vec![0u64; size_x.div_ceil(64)];
+                for (x, _) in row.iter().enumerate().filter(|(_, &ch)| ch == '#') {
+                    bits[x / 64] |= 1 << (x % 64);
+                }
+                bits
+            })
+            .collect();
+
+        Occupancy { size_x, rows }
+    }
+
+    fn is_tree(&self, x: usize, y: usize) -> bool {
+        self.rows[y][x / 64] & (1 << (x % 64)) != 0
+    }
+
+    pub fn count_trees(&self, slope: Slope, edge: Edge) -> usize {
+        Path::new(self.size_x, self.rows.len(), slope, edge)
+            .filter(|&(x, y)| self.is_tree(x, y))
+            .count()
+    }
+
+    /// Maps a slope to the smallest one that visits exactly the same cells. With wrapping only
+    /// `dx` modulo the width matters, clamping treats all leftward moves like going straight
+    /// down, and any step at least as large as the map leaves it right away.
+    fn normalize(&self, slope: Slope, edge: Edge) -> Slope {
+        let (size_x, size_y) = (self.size_x as i64, self.rows.len() as i64);
+        let dx = match edge {
+            Edge::Wrap => slope.dx.rem_euclid(size_x.max(1)),
+            Edge::Clamp => slope.dx.clamp(0, (size_x - 1).max(0)),
+            Edge::Stop => slope.dx.clamp(-1, size_x),
+        };
+        Slope { dx, dy: slope.dy.clamp(-size_y, size_y) }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Objective {
+    Min,
+    Max,
+}
+
+fn steps(slope: Slope) -> (u64, u64, i64, i64) {
+    (slope.dy.unsigned_abs(), slope.dx.unsigned_abs(), slope.dy, slope.dx)
+}
+
+/// Counts trees for every slope with `dx` and `dy` in the given ranges (`dy == 0` is skipped) and
+/// returns the `top` best ones. Ties are broken in favour of the smaller steps, `dy` first.
+pub fn best_slopes(data: &[Vec<char>], dx_range: RangeInclusive<i64>, dy_range: RangeInclusive<i64>,
+                   edge: Edge, objective: Objective, top: usize) -> Vec<(Slope, usize)> {
+    let occupancy = Occupancy::new(data);
+    let mut cache: HashMap<Slope, usize> = HashMap::new();
+
+    let mut results = Vec::new();
+    for dy in dy_range.filter(|&e| e != 0) {
+        for dx in dx_range.clone() {
+            let slope = Slope { dx, dy };
+            let count = *cache.entry(occupancy.normalize(slope, edge))
+                .or_insert_with_key(|&normalized| occupancy.count_trees(normalized, edge));
+            results.push((slope, count));
+        }
+    }
+
+    match objective {
+        Objective::Min => results.sort_by_key(|&(slope, count)| (count, steps(slope))),
+        Objective::Max => results.sort_by_key(|&(slope, count)| (Reverse(count), steps(slope))),
+    }
+    results.truncate(top);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slope::count_trees;
+    use crate::slope::tests::example;
+
+    #[test]
+    fn test_occupancy_matches_count_trees() {
+        let data = example();
+        let occupancy = Occupancy::new(&data);
+        for &edge in &[Edge::Wrap, Edge::Clamp, Edge::Stop] {
+            for dy in -12..=12 {
+                for dx in -25..=25 {
+                    if let Some(slope) = Slope::new(dx, dy) {
+                        let expected = count_trees(&data, slope, edge);
+                        assert_eq!(occupancy.count_trees(slope, edge), expected);
+                        assert_eq!(occupancy.count_trees(occupancy.normalize(slope, edge), edge), expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_best_slopes() {
+        let data = example();
+        let max = best_slopes(&data, 1..=7, 1..=1, Edge::Wrap, Objective::Max, 2);
+        assert_eq!(max, vec![(Slope { dx: 3, dy: 1 }, 7), (Slope { dx: 7, dy: 1 }, 4)]);
+
+        let min = best_slopes(&data, 1..=7, 1..=2, Edge::Wrap, Objective::Min, 3);
+        assert_eq!(min, vec![(Slope { dx: 5, dy: 2 }, 0), (Slope { dx: 2, dy: 1 }, 1), (Slope { dx: 2, dy: 2 }, 1)]);
+        assert!(min.iter().all(|&(slope, count)| count_trees(&data, slope, Edge::Wrap) == count));
+    }
+
+    #[test]
+    fn test_best_slopes_skips_horizontal() {
+        let data = example();
+        let all = best_slopes(&data, -1..=1, -1..=1, Edge::Stop, Objective::Max, 100);
+        assert_eq!(all.len(), 6);
+        assert!(all.iter().all(|(slope, _)| slope.dy != 0));
+    }
+}
@@ -0,0 +1,168 @@
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// Movement of `dx` columns (negative is leftwards) for every `dy` rows (negative is upwards).
+/// Any rational slope can be expressed this way, e.g. half a column per row is `1/2`, and a step
+/// with `|dx| > 1` skips the columns in between.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Slope {
+    pub dx: i64,
+    pub dy: i64,
+}
+
+impl Slope {
+    /// A slope must move vertically, otherwise the traversal would never end.
+    pub fn new(dx: i64, dy: i64) -> Option<Self> {
+        if dy == 0 { None } else { Some(Slope { dx, dy }) }
+    }
+}
+
+impl FromStr for Slope {
+    type Err = String;
+
+    /// Parses `dx/dy`, e.g. `3/1` or `-1/2`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (dx, dy) = s.split_once('/').ok_or_else(|| format!("slope {:?} is not of the form dx/dy", s))?;
+        let parse = |e: &str| i64::from_str(e.trim()).map_err(|_| format!("invalid number {:?} in slope", e));
+        Slope::new(parse(dx)?, parse(dy)?).ok_or_else(|| format!("slope {:?} does not move vertically", s))
+    }
+}
+
+/// What happens when the path leaves the map horizontally. Leaving it vertically always ends the path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    /// The map repeats to the left and right.
+    Wrap,
+    /// The path slides along the edge column.
+    Clamp,
+    /// The path ends.
+    Stop,
+}
+
+impl FromStr for Edge {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrap" => Ok(Edge::Wrap),
+            "clamp" => Ok(Edge::Clamp),
+            "stop" => Ok(Edge::Stop),
+            _ => Err(format!("unknown edge behaviour {:?}", s)),
+        }
+    }
+}
+
+/// Cells visited on the way down (or up), not including the starting cell. Downward paths start at
+/// the top left corner, upward ones at the bottom left.
+pub struct Path {
+    size_x: i64,
+    size_y: i64,
+    slope: Slope,
+    edge: Edge,
+    x: i64,
+    y: i64,
+}
+
+impl Path {
+    pub fn new(size_x: usize, size_y: usize, slope: Slope, edge: Edge) -> Self {
+        let (size_x, size_y) = (size_x as i64, size_y as i64);
+        let y = if slope.dy < 0 { size_y - 1 } else { 0 };
+        Path { size_x, size_y, slope, edge, x: 0, y }
+    }
+}
+
+pub fn path(data: &[Vec<char>], slope: Slope, edge: Edge) -> Path {
+    Path::new(data.first().map_or(0, |row| row.len()), data.len(), slope, edge)
+}
+
+impl Iterator for Path {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.size_x == 0 { return None; }
+
+        let (x, y) = (self.x + self.slope.dx, self.y + self.slope.dy);
+        if y < 0 || y >= self.size_y { return None; }
+
+        let x = match self.edge {
+            Edge::Wrap => x.rem_euclid(self.size_x),
+            Edge::Clamp => x.clamp(0, self.size_x - 1),
+            Edge::Stop if x < 0 || x >= self.size_x => return None,
+            Edge::Stop => x,
+        };
+
+        self.x = x;
+        self.y = y;
+        Some((usize::try_from(x).unwrap(), usize::try_from(y).unwrap()))
+    }
+}
+
+pub fn count_trees(data: &[Vec<char>], slope: Slope, edge: Edge) -> usize {
+    path(data, slope, edge).filter(|&(x, y)| data[y][x] == '#').count()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+    /// The example map from the puzzle, shared with the tests of the other modules.
+    pub(crate) fn example() -> Vec<Vec<char>> {
+        EXAMPLE.lines().map(|e| e.chars().collect()).collect()
+    }
+
+    fn slope(s: &str) -> Slope {
+        Slope::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_count_trees_wrap() {
+        let data = example();
+        let counts: Vec<usize> = ["1/1", "3/1", "5/1", "7/1", "1/2"].iter()
+            .map(|&e| count_trees(&data, slope(e), Edge::Wrap))
+            .collect();
+        assert_eq!(counts, vec![2, 7, 3, 4, 2]);
+    }
+
+    #[test]
+    fn test_leftward_and_upward() {
+        let data = example();
+        let path_left: Vec<(usize, usize)> = path(&data, slope("-3/1"), Edge::Wrap).take(3).collect();
+        assert_eq!(path_left, vec![(8, 1), (5, 2), (2, 3)]);
+
+        let path_up: Vec<(usize, usize)> = path(&data, slope("1/-2"), Edge::Wrap).collect();
+        assert_eq!(path_up, vec![(1, 8), (2, 6), (3, 4), (4, 2), (5, 0)]);
+    }
+
+    #[test]
+    fn test_edges() {
+        let data = example();
+        assert_eq!(path(&data, slope("3/1"), Edge::Stop).count(), 3);
+        assert_eq!(path(&data, slope("-1/1"), Edge::Stop).count(), 0);
+
+        let clamped: Vec<(usize, usize)> = path(&data, slope("4/1"), Edge::Clamp).skip(1).take(3).collect();
+        assert_eq!(clamped, vec![(8, 2), (10, 3), (10, 4)]);
+        assert_eq!(path(&data, slope("-1/1"), Edge::Clamp).count(), 10);
+        assert_eq!(count_trees(&data, slope("-1/1"), Edge::Clamp), 3);
+    }
+
+    #[test]
+    fn test_parse_slope() {
+        assert_eq!(slope("-1/2"), Slope { dx: -1, dy: 2 });
+        assert!(Slope::from_str("1/0").is_err());
+        assert!(Slope::from_str("3").is_err());
+        assert!(Slope::from_str("a/1").is_err());
+        assert_eq!(Edge::from_str("clamp"), Ok(Edge::Clamp));
+    }
+}
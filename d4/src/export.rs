@@ -0,0 +1,190 @@
+use std::io::{self, Write};
+
+use crate::schema::Schema;
+use crate::Passport;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    JsonLines,
+    Csv,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" | "jsonl" => Some(Format::JsonLines),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Which records to export, by strict validity. Malformed records are invalid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    All,
+    Valid,
+    Invalid,
+}
+
+impl Filter {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "all" => Some(Filter::All),
+            "valid" => Some(Filter::Valid),
+            "invalid" => Some(Filter::Invalid),
+            _ => None,
+        }
+    }
+
+    fn accepts(self, is_valid_strict: bool) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Valid => is_valid_strict,
+            Filter::Invalid => !is_valid_strict,
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+/// Writes one record per passport with its field values, both validity checks and the reasons it
+/// failed. CSV columns are the schema fields in declaration order, fields the schema does not know
+/// only show up among the reasons.
+pub struct Exporter<'a, W: Write> {
+    out: W,
+    format: Format,
+    filter: Filter,
+    schema: &'a Schema,
+}
+
+impl<'a, W: Write> Exporter<'a, W> {
+    /// Creates the exporter and outputs the header if the format has one.
+    pub fn new(mut out: W, format: Format, filter: Filter, schema: &'a Schema) -> io::Result<Self> {
+        if format == Format::Csv {
+            let mut columns = vec!["line".to_owned()];
+            columns.extend(schema.fields.iter().map(|e| csv_field(&e.key)));
+            columns.extend(["valid", "valid_strict", "reasons"].iter().map(|&e| e.to_owned()));
+            writeln!(out, "{}", columns.join(","))?;
+        }
+
+        Ok(Exporter { out, format, filter, schema })
+    }
+
+    pub fn write(&mut self, line: usize, passport: &Result<Passport, String>) -> io::Result<()> {
+        let (is_valid, is_valid_strict, reasons) = match passport {
+            Ok(p) => {
+                let reasons: Vec<String> = p.validate(self.schema).into_iter().map(|e| e.message).collect();
+                (p.is_valid(self.schema), p.is_valid_strict(self.schema), reasons)
+            }
+            Err(error) => (false, false, vec![error.clone()]),
+        };
+        if !self.filter.accepts(is_valid_strict) { return Ok(()); }
+
+        match self.format {
+            Format::JsonLines => {
+                let mut fields = Vec::new();
+                if let Ok(p) = passport {
+                    let mut unknown: Vec<&&str> = p.data.keys().filter(|e| self.schema.get(e).is_none()).collect();
+                    unknown.sort();
+                    let keys = self.schema.fields.iter().map(|e| e.key.as_str()).chain(unknown.into_iter().copied());
+                    for key in keys {
+                        if let Some(value) = p.data.get(key) {
+                            fields.push(format!("{}:{}", json_string(key), json_string(value)));
+                        }
+                    }
+                }
+                let reasons: Vec<String> = reasons.iter().map(|e| json_string(e)).collect();
+                writeln!(self.out, "{{\"line\":{},\"fields\":{{{}}},\"valid\":{},\"valid_strict\":{},\"reasons\":[{}]}}",
+                         line, fields.join(","), is_valid, is_valid_strict, reasons.join(","))
+            }
+            Format::Csv => {
+                let mut columns = vec![line.to_string()];
+                columns.extend(self.schema.fields.iter().map(|rule| {
+                    passport.as_ref().ok()
+                        .and_then(|p| p.data.get(rule.key.as_str()))
+                        .map_or_else(String::new, |value| csv_field(value))
+                }));
+                columns.push(is_valid.to_string());
+                columns.push(is_valid_strict.to_string());
+                columns.push(csv_field(&reasons.join("; ")));
+                writeln!(self.out, "{}", columns.join(","))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use std::str::FromStr;
+
+    fn export(format: Format, filter: Filter, records: &[&str]) -> String {
+        let schema = Schema::from_str(include_str!("../schema.txt")).unwrap();
+        let mut out = Vec::new();
+        let mut exporter = Exporter::new(&mut out, format, filter, &schema).unwrap();
+        for (i, record) in records.iter().enumerate() {
+            exporter.write(i + 1, &Passport::try_from(*record)).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    const RECORDS: [&str; 3] = ["pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980\nhcl:#623a2f",
+                                "hgt:190in ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:1937 iyr:2017 foo:1",
+                                "ecl:gry pid"];
+
+    #[test]
+    fn test_json_lines() {
+        assert_eq!(export(Format::JsonLines, Filter::All, &RECORDS),
+                   "{\"line\":1,\"fields\":{\"byr\":\"1980\",\"iyr\":\"2012\",\"eyr\":\"2030\",\"hgt\":\"74in\",\
+                    \"hcl\":\"#623a2f\",\"ecl\":\"grn\",\"pid\":\"087499704\"},\"valid\":true,\"valid_strict\":true,\
+                    \"reasons\":[]}\n\
+                    {\"line\":2,\"fields\":{\"byr\":\"1937\",\"iyr\":\"2017\",\"eyr\":\"2020\",\"hgt\":\"190in\",\
+                    \"hcl\":\"#fffffd\",\"ecl\":\"gry\",\"pid\":\"860033327\",\"foo\":\"1\"},\"valid\":true,\
+                    \"valid_strict\":false,\"reasons\":[\"190 is outside 59..=76\",\"unknown field foo\"]}\n\
+                    {\"line\":3,\"fields\":{},\"valid\":false,\"valid_strict\":false,\
+                    \"reasons\":[\"\\\"pid\\\" is not of the form key:value\"]}\n");
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(export(Format::Csv, Filter::All, &RECORDS),
+                   "line,byr,iyr,eyr,hgt,hcl,ecl,pid,cid,valid,valid_strict,reasons\n\
+                    1,1980,2012,2030,74in,#623a2f,grn,087499704,,true,true,\n\
+                    2,1937,2017,2020,190in,#fffffd,gry,860033327,,true,false,190 is outside 59..=76; unknown field foo\n\
+                    3,,,,,,,,,false,false,\"\"\"pid\"\" is not of the form key:value\"\n");
+    }
+
+    #[test]
+    fn test_filter() {
+        let valid = export(Format::Csv, Filter::Valid, &RECORDS);
+        assert_eq!(valid.lines().skip(1).map(|e| &e[..2]).collect::<Vec<_>>(), vec!["1,"]);
+        let invalid = export(Format::Csv, Filter::Invalid, &RECORDS);
+        assert_eq!(invalid.lines().skip(1).map(|e| &e[..2]).collect::<Vec<_>>(), vec!["2,", "3,"]);
+    }
+}
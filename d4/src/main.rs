@@ -1,100 +1,220 @@
+mod export;
+mod records;
+mod schema;
+mod typed;
+
 use std::collections::HashMap;
-use std::convert::From;
-use std::ops::RangeInclusive;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::str::FromStr;
 
-fn read_file(filename: &str) -> Vec<String> {
-    let lines = std::fs::read_to_string(filename).unwrap();
+use export::{Exporter, Filter, Format};
+use records::{read_records, Record};
+use schema::{ErrorKind, Schema, ValidationError};
+use typed::ValidPassport;
 
-    lines.split("\n\n").map(|e| e.replace('\n', " ")).collect()
+fn read_file(filename: &str) -> Vec<Record> {
+    let file = File::open(filename).unwrap();
+    read_records(io::BufReader::new(file)).map(|e| e.unwrap()).collect()
 }
 
 #[derive(Debug)]
 struct Passport<'a> {
     data: HashMap<&'a str, &'a str>,
+    /// Keys that appeared again after their first occurrence, `data` keeps the last value.
+    duplicates: Vec<&'a str>,
 }
 
-const FIELDS: [&str; 7] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
-const ECLS: [&str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
-
 impl<'a> Passport<'a> {
-    fn is_valid(&self) -> bool {
-        self.data.keys().count() >= 7 && FIELDS.iter().all(|e| self.data.contains_key(e))
+    /// All required fields of the schema are present.
+    fn is_valid(&self, schema: &Schema) -> bool {
+        schema.fields.iter().all(|e| !e.required || self.data.contains_key(e.key.as_str()))
     }
 
-    fn check_in_range(num: &str, range: RangeInclusive<u32>) -> bool {
-        num.parse().map_or(false, |n| range.contains(&n))
+    /// All required fields are present, and every present field that the schema knows is valid.
+    fn is_valid_strict(&self, schema: &Schema) -> bool {
+        self.validate(schema).iter().all(|e| !e.kind.fails_strict())
     }
 
-    fn validate_hgt(height: &str) -> bool {
-        height.strip_suffix("cm").map_or(false, |s| Passport::check_in_range(s, 150..=193)) ||
-            height.strip_suffix("in").map_or(false, |s| Passport::check_in_range(s, 59..=76))
-    }
+    /// Every problem with the passport: missing and invalid fields in schema order, then repeated
+    /// fields, then fields that the schema does not know.
+    fn validate(&self, schema: &Schema) -> Vec<ValidationError> {
+        let error = |field: &str, kind, message| ValidationError { field: field.to_owned(), kind, message };
+        let mut errors = Vec::new();
 
-    fn validate_hcl(hcl: &str) -> bool {
-        hcl.strip_prefix("#").map_or(false, |e| e.chars().all(|e| (e >= '0' && e <= '9') || (e >= 'a' && e <= 'f')))
-    }
+        for rule in &schema.fields {
+            match self.data.get(rule.key.as_str()) {
+                Some(value) => if let Err((kind, message)) = rule.kind.check(value) {
+                    errors.push(error(&rule.key, kind, message));
+                },
+                None if rule.required =>
+                    errors.push(error(&rule.key, ErrorKind::Missing, format!("missing required field {}", rule.key))),
+                None => {}
+            }
+        }
 
-    fn validate_ecl(ecl: &str) -> bool {
-        ECLS.contains(&ecl)
-    }
+        for key in &self.duplicates {
+            errors.push(error(key, ErrorKind::Duplicate, format!("field {} appears more than once", key)));
+        }
 
-    fn validate_pid(pid: &str) -> bool {
-        pid.len() == 9 && pid.chars().all(|e| e.is_numeric())
-    }
+        let mut unknown: Vec<&&str> = self.data.keys().filter(|e| schema.get(e).is_none()).collect();
+        unknown.sort();
+        for key in unknown {
+            errors.push(error(key, ErrorKind::Unknown, format!("unknown field {}", key)));
+        }
 
-    fn is_valid_strict(&self) -> bool {
-        self.is_valid() &&
-            Passport::check_in_range(self.data.get("byr").unwrap(), 1920..=2002) &&
-            Passport::check_in_range(self.data.get("iyr").unwrap(), 2010..=2020) &&
-            Passport::check_in_range(self.data.get("eyr").unwrap(), 2020..=2030) &&
-            Passport::validate_hgt(self.data.get("hgt").unwrap()) &&
-            Passport::validate_hcl(self.data.get("hcl").unwrap()) &&
-            Passport::validate_ecl(self.data.get("ecl").unwrap()) &&
-            Passport::validate_pid(self.data.get("pid").unwrap())
+        errors
     }
 }
 
-impl<'a> From<&'a str> for Passport<'a> {
-    fn from(line: &'a str) -> Self {
+impl<'a> TryFrom<&'a str> for Passport<'a> {
+    type Error = String;
+
+    /// Fails on the first token that is not of the form `key:value` with both sides non-empty.
+    fn try_from(record: &'a str) -> Result<Self, Self::Error> {
         let mut data = HashMap::new();
+        let mut duplicates = Vec::new();
+
+        for entry in record.split_whitespace() {
+            let (key, value) = entry.split_once(':')
+                .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+                .ok_or_else(|| format!("{:?} is not of the form key:value", entry))?;
+            if data.insert(key, value).is_some() {
+                duplicates.push(key);
+            }
+        }
+
+        Ok(Passport { data, duplicates })
+    }
+}
 
-        let entries = line.split([' ', '\n'].as_ref()).filter(|e| !e.is_empty());
+struct Options {
+    schema_file: String,
+    typed: bool,
+    export: Option<Format>,
+    filter: Filter,
+}
 
-        for entry in entries {
-            let splitted: Vec<&str> = entry.split(":").collect();
-            data.insert(splitted[0], splitted[1]);
+/// `--schema FILE` replaces `schema.txt`, `--typed` prints the strictly valid passports in their
+/// normalized form instead of the counts. `--export json|csv` writes every record instead, or only
+/// those selected by `--only valid|invalid`.
+fn parse_args(mut args: impl Iterator<Item=String>) -> Options {
+    let mut options = Options { schema_file: "schema.txt".to_owned(), typed: false, export: None, filter: Filter::All };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_default();
+        match arg.as_str() {
+            "--schema" => options.schema_file = value(),
+            "--typed" => options.typed = true,
+            "--export" => {
+                let format = value();
+                options.export = Some(Format::from_name(&format).unwrap_or_else(|| panic!("Unknown export format: {}", format)));
+            }
+            "--only" => {
+                let filter = value();
+                options.filter = Filter::from_name(&filter).unwrap_or_else(|| panic!("Unknown filter: {}", filter));
+            }
+            _ => panic!("Unknown argument: {}", arg),
         }
+    }
+    options
+}
 
-        Passport { data }
+fn export(data: &[Record], passports: &[Result<Passport, String>], schema: &Schema, format: Format,
+          filter: Filter) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut exporter = Exporter::new(stdout.lock(), format, filter, schema)?;
+    for (record, p) in data.iter().zip(passports) {
+        exporter.write(record.line, p)?;
     }
+    io::stdout().flush()
 }
 
 fn main() {
+    let options = parse_args(std::env::args().skip(1));
     let data = read_file("input");
 
+    let passports: Vec<Result<Passport, String>> = data.iter()
+        .map(|record| Passport::try_from(record.text.as_str()))
+        .collect();
+
+    let schema = Schema::from_str(&std::fs::read_to_string(&options.schema_file).unwrap()).unwrap();
+
+    if options.typed {
+        for (record, p) in data.iter().zip(&passports) {
+            let p = match p {
+                Ok(p) if p.is_valid_strict(&schema) => p,
+                _ => continue,
+            };
+            match ValidPassport::try_from(p) {
+                Ok(p) => println!("{}", p),
+                Err(errors) => for e in errors {
+                    eprintln!("Record at line {}: {}", record.line, e.message);
+                },
+            }
+        }
+        return;
+    }
+
+    if let Some(format) = options.export {
+        export(&data, &passports, &schema, format, options.filter).unwrap();
+        return;
+    }
+
+    // Malformed records are reported and then count as invalid.
+    for (record, p) in data.iter().zip(&passports) {
+        if let Err(e) = p {
+            eprintln!("Record at line {}: {}", record.line, e);
+        }
+    }
+
     let mut count = 0;
     let mut strict_count = 0;
-    for line in &data {
-        let p = Passport::from(line.as_str());
-        if p.is_valid() { count += 1; }
-        if p.is_valid_strict() { strict_count += 1; }
+    let mut histogram: HashMap<(String, ErrorKind), usize> = HashMap::new();
+    for p in passports.iter().flatten() {
+        if p.is_valid(&schema) { count += 1; }
+        if p.is_valid_strict(&schema) { strict_count += 1; }
+
+        for e in p.validate(&schema) {
+            *histogram.entry((e.field, e.kind)).or_insert(0) += 1;
+        }
     }
     println!("{}/{}", count, data.len());
     println!("Strict: {}/{}", strict_count, data.len());
+
+    let mut reasons: Vec<_> = histogram.into_iter().collect();
+    reasons.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    println!("Failure reasons:");
+    for ((field, kind), n) in reasons {
+        println!("{:>6} {} {}", n, field, kind);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn standard() -> Schema {
+        Schema::from_str(include_str!("../schema.txt")).unwrap()
+    }
+
     #[test]
     fn test_from_line() {
         let l1 = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\nbyr:1937 iyr:2017 cid:147 hgt:183cm";
-        let p1 = Passport::from(l1);
+        let p1 = Passport::try_from(l1).unwrap();
         assert_eq!(p1.data.get("ecl").unwrap(), &"gry");
         assert_eq!(p1.data.get("hgt").unwrap(), &"183cm");
     }
 
+    #[test]
+    fn test_malformed_tokens() {
+        assert_eq!(Passport::try_from("ecl:gry pid860033327").unwrap_err(), "\"pid860033327\" is not of the form key:value");
+        assert!(Passport::try_from("ecl:gry :1").is_err());
+        assert!(Passport::try_from("ecl:gry hgt:").is_err());
+        assert_eq!(Passport::try_from("ecl:a:b").unwrap().data.get("ecl").unwrap(), &"a:b");
+    }
+
     #[test]
     fn test_is_valid() {
         let ls = vec!["ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:1937 iyr:2017 cid:147 hgt:183cm",
@@ -102,12 +222,12 @@ mod tests {
                       "hcl:#ae17e1 iyr:2013\neyr:2024\necl:brn pid:760753108 byr:1931\nhgt:179cm",
                       "hcl:#cfa07d eyr:2025 pid:166559648\niyr:2011 ecl:brn hgt:59in"];
 
-        let ps: Vec<Passport> = ls.into_iter().map(Passport::from).collect();
+        let ps: Vec<Passport> = ls.into_iter().map(|e| Passport::try_from(e).unwrap()).collect();
 
-        assert!(ps[0].is_valid());
-        assert!(!ps[1].is_valid());
-        assert!(ps[2].is_valid());
-        assert!(!ps[3].is_valid());
+        assert!(ps[0].is_valid(&standard()));
+        assert!(!ps[1].is_valid(&standard()));
+        assert!(ps[2].is_valid(&standard()));
+        assert!(!ps[3].is_valid(&standard()));
     }
 
     #[test]
@@ -121,15 +241,41 @@ mod tests {
                       "hcl:#888785\nhgt:164cm byr:2001 iyr:2015 cid:88\npid:545766238 ecl:hzl\neyr:2022",
                       "iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719"];
 
-        let ps: Vec<Passport> = ls.into_iter().map(Passport::from).collect();
+        let ps: Vec<Passport> = ls.into_iter().map(|e| Passport::try_from(e).unwrap()).collect();
+
+        assert!(!ps[0].is_valid_strict(&standard()));
+        assert!(!ps[1].is_valid_strict(&standard()));
+        assert!(!ps[2].is_valid_strict(&standard()));
+        assert!(!ps[3].is_valid_strict(&standard()));
+        assert!(ps[4].is_valid_strict(&standard()));
+        assert!(ps[5].is_valid_strict(&standard()));
+        assert!(ps[6].is_valid_strict(&standard()));
+        assert!(ps[7].is_valid_strict(&standard()));
+    }
+
+    #[test]
+    fn test_validate() {
+        let schema = standard();
+        let p = Passport::try_from("hgt:190in ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:1937 iyr:2017 foo:1 ecl:blu").unwrap();
+        let errors = p.validate(&schema);
+        let kinds: Vec<(&str, ErrorKind)> = errors.iter().map(|e| (e.field.as_str(), e.kind)).collect();
+        assert_eq!(kinds, vec![("hgt", ErrorKind::OutOfRange), ("ecl", ErrorKind::Duplicate), ("foo", ErrorKind::Unknown)]);
+        assert_eq!(errors[0].message, "190 is outside 59..=76");
+        assert!(p.is_valid(&schema));
+        assert!(!p.is_valid_strict(&schema));
 
-        assert!(!ps[0].is_valid_strict());
-        assert!(!ps[1].is_valid_strict());
-        assert!(!ps[2].is_valid_strict());
-        assert!(!ps[3].is_valid_strict());
-        assert!(ps[4].is_valid_strict());
-        assert!(ps[5].is_valid_strict());
-        assert!(ps[6].is_valid_strict());
-        assert!(ps[7].is_valid_strict());
+        // Unknown and repeated fields are reported without failing strict validation, the last value wins.
+        let p = Passport::try_from("hgt:190cm ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:1937 iyr:2017 foo:1 ecl:blu").unwrap();
+        let kinds: Vec<ErrorKind> = p.validate(&schema).into_iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![ErrorKind::Duplicate, ErrorKind::Unknown]);
+        assert_eq!(p.data.get("ecl").unwrap(), &"blu");
+        assert!(p.is_valid_strict(&schema));
+
+        let p = Passport::try_from("ecl:gry pid:860033327").unwrap();
+        let missing: Vec<String> = p.validate(&schema).into_iter()
+            .filter(|e| e.kind == ErrorKind::Missing)
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(missing, vec!["byr", "iyr", "eyr", "hgt", "hcl"]);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,88 @@
+use std::io::{self, BufRead};
+
+/// The lines of one passport joined with `\n`, trimmed, and the line it starts on, counting from 1.
+#[derive(Debug, PartialEq)]
+pub struct Record {
+    pub line: usize,
+    pub text: String,
+}
+
+/// Streams the blank line separated records of a reader. Lines may end in `\n`, `\r\n` or a lone
+/// `\r`, any number of blank or whitespace-only lines separate two records.
+pub struct Records<R> {
+    lines: io::Lines<R>,
+    /// Lines that came after a lone `\r` and have not been consumed yet.
+    pending: Vec<String>,
+    line: usize,
+}
+
+pub fn read_records<R: BufRead>(reader: R) -> Records<R> {
+    Records { lines: reader.lines(), pending: Vec::new(), line: 0 }
+}
+
+impl<R: BufRead> Records<R> {
+    fn next_line(&mut self) -> Option<io::Result<String>> {
+        if self.pending.is_empty() {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            // `lines` only strips `\r\n`, whatever `\r` is left separates old Mac style lines.
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+            self.pending = line.split('\r').rev().map(|e| e.to_owned()).collect();
+        }
+
+        self.line += 1;
+        self.pending.pop().map(Ok)
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record: Option<Record> = None;
+
+        while let Some(line) = self.next_line() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let text = line.trim();
+            match &mut record {
+                None if text.is_empty() => {}
+                None => record = Some(Record { line: self.line, text: text.to_owned() }),
+                Some(_) if text.is_empty() => break,
+                Some(record) => {
+                    record.text.push('\n');
+                    record.text.push_str(text);
+                }
+            }
+        }
+
+        record.map(Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(input: &str) -> Vec<Record> {
+        read_records(input.as_bytes()).map(|e| e.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_read_records() {
+        let record = |line, text: &str| Record { line, text: text.to_owned() };
+        let expected = vec![record(1, "a:1 b:2\nc:3"), record(4, "d:4"), record(8, "e:5")];
+
+        assert_eq!(records("a:1 b:2\nc:3\n\nd:4\n\n\n\ne:5"), expected);
+        assert_eq!(records("a:1 b:2\r\nc:3\r\n\r\nd:4\r\n\r\n\r\n\r\ne:5\r\n"), expected);
+        assert_eq!(records("a:1 b:2\rc:3\r\rd:4\r\r\r\re:5\r"), expected);
+        assert_eq!(records("a:1 b:2  \n\tc:3\n   \nd:4 \n\t\n\n \ne:5\n\n"), expected);
+        assert_eq!(records("\n\n\na:1"), vec![record(4, "a:1")]);
+        assert!(records("\n \r\n").is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use regex::Regex;
+
+/// What a field value has to look like, see `schema.txt` for the syntax.
+#[derive(Debug)]
+pub enum FieldKind {
+    Any,
+    IntRange(RangeInclusive<u32>),
+    /// A number followed by one of the units, each unit with its own range.
+    Units(Vec<(String, RangeInclusive<u32>)>),
+    /// The regex is anchored so that the whole value has to match, `source` is the pattern as written.
+    Pattern { source: String, regex: Regex },
+    OneOf(Vec<String>),
+    Digits(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ErrorKind {
+    Missing,
+    Duplicate,
+    Unknown,
+    NotANumber,
+    OutOfRange,
+    UnknownUnit,
+    PatternMismatch,
+    NotAllowed,
+    WrongLength,
+}
+
+impl ErrorKind {
+    /// Unknown and repeated fields are only reported, they do not make a passport invalid.
+    pub fn fails_strict(self) -> bool {
+        self != ErrorKind::Unknown && self != ErrorKind::Duplicate
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ErrorKind::Missing => "missing",
+            ErrorKind::Duplicate => "duplicate",
+            ErrorKind::Unknown => "unknown field",
+            ErrorKind::NotANumber => "not a number",
+            ErrorKind::OutOfRange => "out of range",
+            ErrorKind::UnknownUnit => "unknown unit",
+            ErrorKind::PatternMismatch => "pattern mismatch",
+            ErrorKind::NotAllowed => "not allowed",
+            ErrorKind::WrongLength => "wrong length",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A problem with one field of a passport, `message` is meant for humans.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    pub field: String,
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+fn check_in_range(num: &str, range: &RangeInclusive<u32>) -> Result<(), (ErrorKind, String)> {
+    let n: u32 = num.parse().map_err(|_| (ErrorKind::NotANumber, format!("{:?} is not a number", num)))?;
+    if range.contains(&n) {
+        Ok(())
+    } else {
+        Err((ErrorKind::OutOfRange, format!("{} is outside {}..={}", n, range.start(), range.end())))
+    }
+}
+
+impl FieldKind {
+    pub fn check(&self, value: &str) -> Result<(), (ErrorKind, String)> {
+        match self {
+            FieldKind::Any => Ok(()),
+            FieldKind::IntRange(range) => check_in_range(value, range),
+            FieldKind::Units(units) => {
+                let (number, range) = units.iter()
+                    .find_map(|(unit, range)| value.strip_suffix(unit.as_str()).map(|e| (e, range)))
+                    .ok_or_else(|| {
+                        let names: Vec<&str> = units.iter().map(|e| e.0.as_str()).collect();
+                        (ErrorKind::UnknownUnit, format!("{:?} has none of the units {}", value, names.join(", ")))
+                    })?;
+                check_in_range(number, range)
+            }
+            FieldKind::Pattern { source, regex } => if regex.is_match(value) {
+                Ok(())
+            } else {
+                Err((ErrorKind::PatternMismatch, format!("{:?} does not match {}", value, source)))
+            },
+            FieldKind::OneOf(values) => if values.iter().any(|e| e == value) {
+                Ok(())
+            } else {
+                Err((ErrorKind::NotAllowed, format!("{:?} is not one of {}", value, values.join(", "))))
+            },
+            FieldKind::Digits(len) => if !value.chars().all(|e| e.is_ascii_digit()) {
+                Err((ErrorKind::NotANumber, format!("{:?} is not all digits", value)))
+            } else if value.len() != *len {
+                Err((ErrorKind::WrongLength, format!("{:?} has {} digits instead of {}", value, value.len(), len)))
+            } else {
+                Ok(())
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FieldRule {
+    pub key: String,
+    pub required: bool,
+    pub kind: FieldKind,
+}
+
+/// The fields a passport may have, in the order they were declared.
+#[derive(Debug)]
+pub struct Schema {
+    pub fields: Vec<FieldRule>,
+}
+
+impl Schema {
+    pub fn get(&self, key: &str) -> Option<&FieldRule> {
+        self.fields.iter().find(|e| e.key == key)
+    }
+}
+
+fn parse_range(s: &str) -> Result<RangeInclusive<u32>, String> {
+    let (start, end) = s.split_once("..=").ok_or_else(|| format!("range {:?} is not of the form min..=max", s))?;
+    let parse = |e: &str| u32::from_str(e).map_err(|_| format!("invalid number {:?} in range", e));
+    Ok(parse(start)?..=parse(end)?)
+}
+
+fn parse_kind(kind: &str, args: &[&str]) -> Result<FieldKind, String> {
+    match (kind, args) {
+        ("int", [range]) => Ok(FieldKind::IntRange(parse_range(range)?)),
+        ("units", units) if !units.is_empty() => units.iter()
+            .map(|e| {
+                let (unit, range) = e.split_once(':').ok_or_else(|| format!("unit {:?} is not of the form unit:range", e))?;
+                Ok((unit.to_owned(), parse_range(range)?))
+            })
+            .collect::<Result<_, String>>()
+            .map(FieldKind::Units),
+        ("regex", [pattern]) => Regex::new(&format!("^(?:{})$", pattern))
+            .map(|regex| FieldKind::Pattern { source: pattern.to_string(), regex })
+            .map_err(|e| format!("invalid regex: {}", e)),
+        ("enum", values) if !values.is_empty() => Ok(FieldKind::OneOf(values.iter().map(|&e| e.to_owned()).collect())),
+        ("digits", [len]) => usize::from_str(len)
+            .map(FieldKind::Digits)
+            .map_err(|_| format!("invalid length {:?}", len)),
+        ("int", _) | ("units", _) | ("regex", _) | ("enum", _) | ("digits", _) =>
+            Err(format!("wrong number of arguments for {}", kind)),
+        _ => Err(format!("unknown type {:?}", kind)),
+    }
+}
+
+impl FromStr for Schema {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields: Vec<FieldRule> = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue; }
+
+            let error = |e: String| format!("schema line {}: {}", i + 1, e);
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let required = match tokens.get(1) {
+                Some(&"required") => true,
+                Some(&"optional") => false,
+                _ => return Err(error("expected required or optional after the key".to_owned())),
+            };
+            let kind = match tokens.get(2) {
+                Some(kind) => parse_kind(kind, &tokens[3..]).map_err(error)?,
+                None => FieldKind::Any,
+            };
+
+            if fields.iter().any(|e| e.key == tokens[0]) {
+                return Err(error(format!("field {} declared twice", tokens[0])));
+            }
+            fields.push(FieldRule { key: tokens[0].to_owned(), required, kind });
+        }
+
+        Ok(Schema { fields })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standard() -> Schema {
+        Schema::from_str(include_str!("../schema.txt")).unwrap()
+    }
+
+    #[test]
+    fn test_standard_schema() {
+        let schema = standard();
+        let keys: Vec<&str> = schema.fields.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"]);
+        assert!(!schema.get("cid").unwrap().required);
+        assert!(schema.get("xyz").is_none());
+    }
+
+    #[test]
+    fn test_field_kinds() {
+        let schema = standard();
+        let matches = |key, value| schema.get(key).unwrap().kind.check(value).is_ok();
+
+        assert!(matches("byr", "2002"));
+        assert!(!matches("byr", "2003"));
+        assert!(matches("hgt", "60in"));
+        assert!(matches("hgt", "190cm"));
+        assert!(!matches("hgt", "190in"));
+        assert!(!matches("hgt", "190"));
+        assert!(matches("hcl", "#123abc"));
+        assert!(!matches("hcl", "#123abz"));
+        assert!(!matches("hcl", "123abc"));
+        assert!(!matches("hcl", "#123abcd"));
+        assert!(matches("ecl", "brn"));
+        assert!(!matches("ecl", "wat"));
+        assert!(matches("pid", "000000001"));
+        assert!(!matches("pid", "0123456789"));
+        assert!(matches("cid", "anything"));
+    }
+
+    #[test]
+    fn test_check_messages() {
+        let schema = standard();
+        let check = |key, value| schema.get(key).unwrap().kind.check(value).unwrap_err();
+
+        assert_eq!(check("hgt", "190in"), (ErrorKind::OutOfRange, "190 is outside 59..=76".to_owned()));
+        assert_eq!(check("hgt", "190"), (ErrorKind::UnknownUnit, "\"190\" has none of the units cm, in".to_owned()));
+        assert_eq!(check("byr", "19x0"), (ErrorKind::NotANumber, "\"19x0\" is not a number".to_owned()));
+        assert_eq!(check("hcl", "123abc"), (ErrorKind::PatternMismatch, "\"123abc\" does not match #[0-9a-f]{6}".to_owned()));
+        assert_eq!(check("ecl", "wat").0, ErrorKind::NotAllowed);
+        assert_eq!(check("pid", "0123456789"), (ErrorKind::WrongLength, "\"0123456789\" has 10 digits instead of 9".to_owned()));
+        assert_eq!(check("pid", "01234567a").0, ErrorKind::NotANumber);
+    }
+
+    #[test]
+    fn test_schema_errors() {
+        assert_eq!(Schema::from_str("byr maybe").unwrap_err(),
+                   "schema line 1: expected required or optional after the key");
+        assert_eq!(Schema::from_str("# comment\nbyr required int 10").unwrap_err(),
+                   "schema line 2: range \"10\" is not of the form min..=max");
+        assert_eq!(Schema::from_str("byr required float").unwrap_err(), "schema line 1: unknown type \"float\"");
+        assert_eq!(Schema::from_str("pid required digits").unwrap_err(),
+                   "schema line 1: wrong number of arguments for digits");
+        assert_eq!(Schema::from_str("a optional\na required").unwrap_err(), "schema line 2: field a declared twice");
+    }
+}
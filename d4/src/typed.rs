@@ -0,0 +1,250 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::schema::{ErrorKind, ValidationError};
+use crate::Passport;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Height {
+    Cm(u16),
+    In(u16),
+}
+
+impl FromStr for Height {
+    type Err = (ErrorKind, String);
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |e: &str| u16::from_str(e).map_err(|_| (ErrorKind::NotANumber, format!("{:?} is not a number", e)));
+        if let Some(cm) = s.strip_suffix("cm") {
+            number(cm).map(Height::Cm)
+        } else if let Some(inches) = s.strip_suffix("in") {
+            number(inches).map(Height::In)
+        } else {
+            Err((ErrorKind::UnknownUnit, format!("{:?} has no unit", s)))
+        }
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Height::Cm(cm) => write!(f, "{}cm", cm),
+            Height::In(inches) => write!(f, "{}in", inches),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl FromStr for Rgb {
+    type Err = (ErrorKind, String);
+
+    /// Parses `#rrggbb`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#')
+            .filter(|e| e.len() == 6 && e.chars().all(|ch| ch.is_ascii_hexdigit()))
+            .ok_or_else(|| (ErrorKind::PatternMismatch, format!("{:?} is not a #rrggbb color", s)))?;
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+        Ok(Rgb { r: channel(0), g: channel(2), b: channel(4) })
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EyeColor {
+    Amber,
+    Blue,
+    Brown,
+    Gray,
+    Green,
+    Hazel,
+    Other,
+}
+
+const EYE_COLORS: [(&str, EyeColor); 7] = [("amb", EyeColor::Amber), ("blu", EyeColor::Blue), ("brn", EyeColor::Brown),
+    ("gry", EyeColor::Gray), ("grn", EyeColor::Green), ("hzl", EyeColor::Hazel), ("oth", EyeColor::Other)];
+
+impl FromStr for EyeColor {
+    type Err = (ErrorKind, String);
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EYE_COLORS.iter()
+            .find(|e| e.0 == s)
+            .map(|e| e.1)
+            .ok_or_else(|| (ErrorKind::NotAllowed, format!("{:?} is not an eye color", s)))
+    }
+}
+
+impl fmt::Display for EyeColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", EYE_COLORS.iter().find(|e| e.1 == *self).unwrap().0)
+    }
+}
+
+/// A passport with every field parsed into its domain type. Whether the values are within the
+/// allowed ranges is up to the schema, check `is_valid_strict` before converting.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidPassport {
+    pub birth_year: u16,
+    pub issue_year: u16,
+    pub expiration_year: u16,
+    pub height: Height,
+    pub hair_color: Rgb,
+    pub eye_color: EyeColor,
+    /// One decimal digit per element.
+    pub passport_id: [u8; 9],
+    pub country_id: Option<String>,
+}
+
+/// Parses a field into its domain type, or records why that failed.
+fn parse_field<T>(passport: &Passport, key: &str, errors: &mut Vec<ValidationError>) -> Option<T>
+    where T: FromStr<Err=(ErrorKind, String)> {
+    let result = parse_value(passport, key);
+    result.map_err(|e| errors.push(e)).ok()
+}
+
+fn parse_value<T>(passport: &Passport, key: &str) -> Result<T, ValidationError>
+    where T: FromStr<Err=(ErrorKind, String)> {
+    let value = passport.data.get(key).ok_or_else(|| ValidationError {
+        field: key.to_owned(),
+        kind: ErrorKind::Missing,
+        message: format!("missing required field {}", key),
+    })?;
+    T::from_str(value).map_err(|(kind, message)| ValidationError { field: key.to_owned(), kind, message })
+}
+
+/// Newtypes so that years and the passport id can go through `parse_field` like the other fields.
+struct Year(u16);
+
+impl FromStr for Year {
+    type Err = (ErrorKind, String);
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u16::from_str(s).map(Year).map_err(|_| (ErrorKind::NotANumber, format!("{:?} is not a number", s)))
+    }
+}
+
+struct PassportId([u8; 9]);
+
+impl FromStr for PassportId {
+    type Err = (ErrorKind, String);
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits: Vec<u8> = s.chars()
+            .map(|e| e.to_digit(10).map(|d| d as u8))
+            .collect::<Option<_>>()
+            .ok_or_else(|| (ErrorKind::NotANumber, format!("{:?} is not all digits", s)))?;
+        <[u8; 9]>::try_from(digits.as_slice())
+            .map(PassportId)
+            .map_err(|_| (ErrorKind::WrongLength, format!("{:?} does not have 9 digits", s)))
+    }
+}
+
+impl<'a> TryFrom<&Passport<'a>> for ValidPassport {
+    type Error = Vec<ValidationError>;
+
+    /// Converts every field, failing with all fields that do not convert.
+    fn try_from(passport: &Passport<'a>) -> Result<Self, Self::Error> {
+        let mut errors = Vec::new();
+        let fields = (
+            parse_field::<Year>(passport, "byr", &mut errors),
+            parse_field::<Year>(passport, "iyr", &mut errors),
+            parse_field::<Year>(passport, "eyr", &mut errors),
+            parse_field(passport, "hgt", &mut errors),
+            parse_field(passport, "hcl", &mut errors),
+            parse_field(passport, "ecl", &mut errors),
+            parse_field::<PassportId>(passport, "pid", &mut errors),
+        );
+
+        match fields {
+            (Some(byr), Some(iyr), Some(eyr), Some(height), Some(hair_color), Some(eye_color), Some(pid)) => Ok(ValidPassport {
+                birth_year: byr.0,
+                issue_year: iyr.0,
+                expiration_year: eyr.0,
+                height,
+                hair_color,
+                eye_color,
+                passport_id: pid.0,
+                country_id: passport.data.get("cid").map(|&e| e.to_owned()),
+            }),
+            _ => Err(errors),
+        }
+    }
+}
+
+/// Writes the passport back in the `key:value` input format.
+impl fmt::Display for ValidPassport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pid: String = self.passport_id.iter().map(|d| char::from(b'0' + d)).collect();
+        write!(f, "byr:{} iyr:{} eyr:{} hgt:{} hcl:{} ecl:{} pid:{}", self.birth_year, self.issue_year,
+               self.expiration_year, self.height, self.hair_color, self.eye_color, pid)?;
+        if let Some(cid) = &self.country_id {
+            write!(f, " cid:{}", cid)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_types() {
+        assert_eq!(Height::from_str("183cm"), Ok(Height::Cm(183)));
+        assert_eq!(Height::from_str("60in"), Ok(Height::In(60)));
+        assert_eq!(Height::from_str("60").unwrap_err().0, ErrorKind::UnknownUnit);
+        assert_eq!(Height::from_str("xcm").unwrap_err().0, ErrorKind::NotANumber);
+        assert_eq!(Rgb::from_str("#a97842"), Ok(Rgb { r: 0xa9, g: 0x78, b: 0x42 }));
+        assert!(Rgb::from_str("#a9784").is_err());
+        assert_eq!(EyeColor::from_str("hzl"), Ok(EyeColor::Hazel));
+        assert_eq!(EyeColor::Hazel.to_string(), "hzl");
+        assert_eq!(PassportId::from_str("087499704").unwrap().0, [0, 8, 7, 4, 9, 9, 7, 0, 4]);
+        assert_eq!(PassportId::from_str("0874997041").err().map(|e| e.0), Some(ErrorKind::WrongLength));
+        assert_eq!(PassportId::from_str("08749970a").err().map(|e| e.0), Some(ErrorKind::NotANumber));
+    }
+
+    #[test]
+    fn test_try_from() {
+        let p = Passport::try_from("pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980\nhcl:#623a2f").unwrap();
+        let valid = ValidPassport::try_from(&p).unwrap();
+        assert_eq!(valid, ValidPassport {
+            birth_year: 1980,
+            issue_year: 2012,
+            expiration_year: 2030,
+            height: Height::In(74),
+            hair_color: Rgb { r: 0x62, g: 0x3a, b: 0x2f },
+            eye_color: EyeColor::Green,
+            passport_id: [0, 8, 7, 4, 9, 9, 7, 0, 4],
+            country_id: None,
+        });
+        assert_eq!(valid.to_string(), "byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704");
+
+        let p = Passport::try_from("pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f foo:1").unwrap();
+        assert_eq!(ValidPassport::try_from(&p), Ok(valid));
+
+        // Ranges are up to the schema, only values that have no domain type fail.
+        let p = Passport::try_from("eyr:1972 cid:100\nhcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926").unwrap();
+        assert!(ValidPassport::try_from(&p).is_err());
+        let p = Passport::try_from("eyr:1972 hcl:#18171d ecl:xyz hgt:170 pid:0123 byr:19x6").unwrap();
+        let errors: Vec<(String, ErrorKind)> = ValidPassport::try_from(&p).unwrap_err().into_iter()
+            .map(|e| (e.field, e.kind))
+            .collect();
+        assert_eq!(errors, vec![("byr".to_owned(), ErrorKind::NotANumber), ("iyr".to_owned(), ErrorKind::Missing),
+                                ("hgt".to_owned(), ErrorKind::UnknownUnit), ("ecl".to_owned(), ErrorKind::NotAllowed),
+                                ("pid".to_owned(), ErrorKind::WrongLength)]);
+        let p = Passport::try_from("byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:186cm").unwrap();
+        assert_eq!(ValidPassport::try_from(&p).unwrap_err()[0].kind, ErrorKind::NotANumber);
+    }
+}
@@ -0,0 +1,83 @@
+use std::ops::Range;
+use std::str::FromStr;
+
+/// The seating of an aircraft. Both counts are powers of two, so that a pass has one `F`/`B`
+/// letter per halving of the rows followed by one `L`/`R` letter per halving of the columns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SeatLayout {
+    rows: usize,
+    columns: usize,
+}
+
+impl SeatLayout {
+    pub const STANDARD: SeatLayout = SeatLayout { rows: 128, columns: 8 };
+
+    pub fn new(rows: usize, columns: usize) -> Option<Self> {
+        if rows.is_power_of_two() && columns.is_power_of_two() {
+            Some(SeatLayout { rows, columns })
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Number of `F`/`B` letters in a pass.
+    pub fn row_letters(&self) -> usize {
+        self.rows.trailing_zeros() as usize
+    }
+
+    /// Number of `L`/`R` letters in a pass.
+    pub fn column_letters(&self) -> usize {
+        self.columns.trailing_zeros() as usize
+    }
+
+    pub fn seat_id(&self, row: usize, column: usize) -> usize {
+        row * self.columns + column
+    }
+
+    /// The IDs of all seats, from the front left to the back right.
+    pub fn seat_ids(&self) -> Range<usize> {
+        0..self.rows * self.columns
+    }
+}
+
+impl FromStr for SeatLayout {
+    type Err = String;
+
+    /// Parses `<rows>x<columns>`, e.g. `128x8`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rows, columns) = s.split_once('x').ok_or_else(|| format!("layout {:?} is not of the form RxC", s))?;
+        let parse = |e: &str| usize::from_str(e).map_err(|_| format!("invalid number {:?} in layout", e));
+        SeatLayout::new(parse(rows)?, parse(columns)?)
+            .ok_or_else(|| format!("layout {:?} does not have power of two dimensions", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let layout = SeatLayout::STANDARD;
+        assert_eq!((layout.row_letters(), layout.column_letters()), (7, 3));
+        assert_eq!(layout.seat_id(44, 5), 357);
+        assert_eq!(layout.seat_ids(), 0..1024);
+
+        let layout = SeatLayout::from_str("32x4").unwrap();
+        assert_eq!((layout.row_letters(), layout.column_letters()), (5, 2));
+        assert_eq!(layout.seat_id(3, 1), 13);
+
+        assert_eq!(SeatLayout::from_str("1x1").unwrap().seat_ids(), 0..1);
+        assert!(SeatLayout::from_str("100x8").is_err());
+        assert!(SeatLayout::from_str("0x8").is_err());
+        assert!(SeatLayout::from_str("128").is_err());
+    }
+}
@@ -1,11 +1,19 @@
+mod layout;
 mod method_two;
+mod pass;
+mod seatmap;
 
 use std::collections::HashSet;
 use std::fs::File;
 use std::io;
 use std::io::BufRead;
 use std::iter::FromIterator;
+use std::str::FromStr;
+
+use layout::SeatLayout;
 use method_two::method_two;
+use pass::BoardingPass;
+use seatmap::SeatMap;
 
 fn read_file(filename: &str) -> io::Result<io::Lines<io::BufReader<File>>> {
     let f = File::open(filename)?;
@@ -13,36 +21,12 @@ fn read_file(filename: &str) -> io::Result<io::Lines<io::BufReader<File>>> {
     Ok(io::BufReader::new(f).lines())
 }
 
-fn process_line(line: &str) -> (usize, usize) {
-    let (mut lower_r, mut upper_r) = (0, 128);
-    for ch in line.chars().take_while(|&e| e == 'F' || e == 'B') {
-        if ch == 'F' {
-            upper_r -= (upper_r - lower_r) / 2;
-        } else if ch == 'B' {
-            lower_r += (upper_r - lower_r) / 2;
-        }
-    }
-
-    let (mut lower_c, mut upper_c) = (0, 8);
-
-    for ch in line.chars().skip_while(|&e| e == 'F' || e == 'B') {
-        if ch == 'L' {
-            upper_c -= (upper_c - lower_c) / 2;
-        } else if ch == 'R' {
-            lower_c += (upper_c - lower_c) / 2;
-        }
-    }
-
-    (lower_r, lower_c)
-}
-
-fn method_one(lines: impl Iterator<Item=String>) {
+fn method_one(passes: impl Iterator<Item=BoardingPass>, layout: SeatLayout) {
     let mut max = 0;
-    let mut set: HashSet<usize> = HashSet::from_iter(0..=938);
+    let mut set: HashSet<usize> = HashSet::from_iter(layout.seat_ids());
 
-    for line in lines {
-        let (row, col) = process_line(&line);
-        let id = row * 8 + col;
+    for pass in passes {
+        let id = pass.seat_id();
         if id > max {
             max = id
         }
@@ -56,8 +40,102 @@ fn method_one(lines: impl Iterator<Item=String>) {
     println!("Missing: {:?}", sorted);
 }
 
+/// Seats to print the passes of, either as `row,column` or as a seat ID.
+enum Seat {
+    Position(usize, usize),
+    Id(usize),
+}
+
+struct Options {
+    layout: SeatLayout,
+    method_one: bool,
+    map: bool,
+    encode: Vec<Seat>,
+}
+
+/// `--layout RxC` sets the number of rows and columns, `--method one` lists all free seats instead
+/// of computing the missing one from the ID sum. `--encode row,column` and `--encode-id ID` print
+/// the pass of a seat instead, and can be repeated. `--map` draws the cabin and lists free seats and
+/// duplicate passes.
+fn parse_args(mut args: impl Iterator<Item=String>) -> Options {
+    let mut options = Options { layout: SeatLayout::STANDARD, method_one: false, map: false, encode: Vec::new() };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_default();
+        match arg.as_str() {
+            "--layout" => options.layout = SeatLayout::from_str(&value()).unwrap(),
+            "--encode" => {
+                let seat = value();
+                let (row, column) = seat.split_once(',').unwrap_or_else(|| panic!("Seat {:?} is not of the form row,column", seat));
+                options.encode.push(Seat::Position(usize::from_str(row).unwrap(), usize::from_str(column).unwrap()));
+            }
+            "--map" => options.map = true,
+            "--encode-id" => options.encode.push(Seat::Id(usize::from_str(&value()).unwrap())),
+            "--method" => options.method_one = match value().as_str() {
+                "one" => true,
+                "two" => false,
+                other => panic!("Unknown method: {}", other),
+            },
+            _ => panic!("Unknown argument: {}", arg),
+        }
+    }
+    options
+}
+
+fn print_map(map: &SeatMap) {
+    print!("{}", map.render());
+
+    println!("Gaps:");
+    for (start, len) in map.gaps() {
+        println!("  start {}, length {}", start, len);
+    }
+    println!("Free seats between two occupied ones: {:?}", map.enclosed_seats());
+
+    for duplicate in map.duplicates() {
+        println!("Duplicate pass {} for seat {} on line {}, first seen on line {}", duplicate.pass,
+                 duplicate.pass.seat_id(), duplicate.line, duplicate.first_line);
+    }
+}
+
 fn main() {
+    let options = parse_args(std::env::args().skip(1));
+    let layout = options.layout;
+
+    if !options.encode.is_empty() {
+        for seat in options.encode {
+            let pass = match seat {
+                Seat::Position(row, column) => BoardingPass::new(row, column, layout),
+                Seat::Id(id) => BoardingPass::from_seat_id(id, layout),
+            };
+            match pass {
+                Ok(pass) => println!("{} (row {}, column {}, seat ID {})", pass, pass.row(), pass.column(), pass.seat_id()),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        return;
+    }
+
     if let Ok(lines) = read_file("input") {
-        method_two(lines.map(|e| e.unwrap()));
+        // Invalid passes are reported and left out, the methods only see well-formed ones.
+        let passes = lines.map(|e| e.unwrap())
+            .enumerate()
+            .filter_map(|(i, line)| match BoardingPass::parse(&line, layout) {
+                Ok(pass) => Some((i + 1, pass)),
+                Err(e) => {
+                    eprintln!("Line {}: {}", i + 1, e);
+                    None
+                }
+            });
+
+        if options.map {
+            print_map(&SeatMap::new(layout, passes));
+            return;
+        }
+
+        let passes = passes.map(|(_, pass)| pass);
+        if options.method_one {
+            method_one(passes, layout);
+        } else {
+            method_two(passes);
+        }
     }
 }
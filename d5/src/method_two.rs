@@ -1,28 +1,22 @@
-fn parse_line(line: &str) -> (usize, usize) {
-    let row: String = line.chars()
-        .take_while(|&e| e == 'F' || e == 'B')
-        .map(|e| if e == 'F' { '0' } else { '1' }).collect();
-    let row_int = usize::from_str_radix(&row, 2).unwrap();
-
-    let col: String = line.chars()
-        .skip_while(|&e| e == 'F' || e == 'B')
-        .map(|e| if e == 'L' { '0' } else { '1' }).collect();
-    let col_int = usize::from_str_radix(&col, 2).unwrap();
-
-    (row_int, col_int)
-}
+use std::collections::HashSet;
+
+use crate::pass::BoardingPass;
 
+/// Sum of `lower..=upper`.
 fn sum_interval(lower: usize, upper: usize) -> usize {
-    (upper * (upper + 1) / 2) - (lower * (lower + 1) / 2)
+    (upper * (upper + 1) / 2) - (lower * lower.saturating_sub(1) / 2)
 }
 
-pub fn method_two(lines: impl Iterator<Item=String>) {
-    let (mut min, mut max) = (usize::max_value(), usize::min_value());
+/// The highest seat ID and the missing one, found from the sum of the distinct IDs. A repeated pass
+/// is counted once, see `SeatMap::duplicates` for reporting them. `None` without any passes.
+fn max_and_missing(passes: impl Iterator<Item=BoardingPass>) -> Option<(usize, usize)> {
+    let (mut min, mut max) = (usize::MAX, usize::MIN);
     let mut sum = 0;
+    let mut seen = HashSet::new();
 
-    for line in lines {
-        let (row, col) = parse_line(&line);
-        let id = row * 8 + col;
+    for pass in passes {
+        let id = pass.seat_id();
+        if !seen.insert(id) { continue; }
 
         if min > id {
             min = id
@@ -35,6 +29,47 @@ pub fn method_two(lines: impl Iterator<Item=String>) {
         sum += id;
     }
 
-    println!("Max: {}", max);
-    println!("Missing: {}", sum_interval(min, max) - sum)
-}
\ No newline at end of file
+    if min > max { return None; }
+    Some((max, sum_interval(min, max) - sum))
+}
+
+pub fn method_two(passes: impl Iterator<Item=BoardingPass>) {
+    match max_and_missing(passes) {
+        Some((max, missing)) => {
+            println!("Max: {}", max);
+            println!("Missing: {}", missing)
+        }
+        None => println!("No valid passes"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use crate::layout::SeatLayout;
+
+    fn passes(ids: &[usize]) -> impl Iterator<Item=BoardingPass> + '_ {
+        ids.iter().map(|&id| BoardingPass::from_seat_id(id, SeatLayout::STANDARD).unwrap())
+    }
+
+    #[test]
+    fn test_max_and_missing() {
+        assert_eq!(max_and_missing(passes(&[3, 6, 5, 7])), Some((7, 4)));
+        assert_eq!(max_and_missing(passes(&[])), None);
+    }
+
+    #[test]
+    fn test_repeated_passes() {
+        let pass = BoardingPass::from_str("FBFBBFFRLR").unwrap();
+        assert_eq!(max_and_missing(vec![pass; 3].into_iter()), Some((357, 0)));
+        assert_eq!(max_and_missing(passes(&[3, 5, 5, 6, 3])), Some((6, 4)));
+    }
+
+    #[test]
+    fn test_sum_interval() {
+        assert_eq!(sum_interval(0, 4), 10);
+        assert_eq!(sum_interval(3, 5), 12);
+        assert_eq!(sum_interval(7, 7), 7);
+    }
+}
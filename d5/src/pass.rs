@@ -0,0 +1,145 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::layout::SeatLayout;
+
+/// A validated boarding pass, printed back as its `F`/`B`/`L`/`R` letters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoardingPass {
+    row: usize,
+    column: usize,
+    layout: SeatLayout,
+}
+
+impl BoardingPass {
+    pub fn new(row: usize, column: usize, layout: SeatLayout) -> Result<Self, String> {
+        if row >= layout.rows() {
+            Err(format!("row {} is outside 0..{}", row, layout.rows()))
+        } else if column >= layout.columns() {
+            Err(format!("column {} is outside 0..{}", column, layout.columns()))
+        } else {
+            Ok(BoardingPass { row, column, layout })
+        }
+    }
+
+    pub fn from_seat_id(id: usize, layout: SeatLayout) -> Result<Self, String> {
+        if layout.seat_ids().contains(&id) {
+            BoardingPass::new(id / layout.columns(), id % layout.columns(), layout)
+        } else {
+            Err(format!("seat ID {} is outside {:?}", id, layout.seat_ids()))
+        }
+    }
+
+    /// Accepts exactly `layout.row_letters()` of `F`/`B` followed by `layout.column_letters()` of `L`/`R`.
+    pub fn parse(s: &str, layout: SeatLayout) -> Result<Self, String> {
+        let expected = layout.row_letters() + layout.column_letters();
+        let len = s.chars().count();
+        if len != expected {
+            return Err(format!("pass {:?} has {} letters instead of {}", s, len, expected));
+        }
+
+        let (mut row, mut column) = (0, 0);
+        for (i, ch) in s.chars().enumerate() {
+            let is_row = i < layout.row_letters();
+            let (zero, one) = if is_row { ('F', 'B') } else { ('L', 'R') };
+            let bit = if ch == zero {
+                0
+            } else if ch == one {
+                1
+            } else {
+                return Err(format!("pass {:?} has {:?} at position {} instead of {} or {}", s, ch, i + 1, zero, one));
+            };
+
+            let value = if is_row { &mut row } else { &mut column };
+            *value = *value * 2 + bit;
+        }
+
+        Ok(BoardingPass { row, column, layout })
+    }
+
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn seat_id(&self) -> usize {
+        self.layout.seat_id(self.row, self.column)
+    }
+}
+
+/// Parses passes of the standard 128x8 layout.
+impl FromStr for BoardingPass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BoardingPass::parse(s, SeatLayout::STANDARD)
+    }
+}
+
+impl fmt::Display for BoardingPass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letters = |value: usize, count: usize, zero: char, one: char| -> String {
+            (0..count).rev().map(|i| if value >> i & 1 == 0 { zero } else { one }).collect()
+        };
+        write!(f, "{}{}", letters(self.row, self.layout.row_letters(), 'F', 'B'),
+               letters(self.column, self.layout.column_letters(), 'L', 'R'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let pass = BoardingPass::from_str("FBFBBFFRLR").unwrap();
+        assert_eq!((pass.row(), pass.column(), pass.seat_id()), (44, 5, 357));
+
+        let position = |s: &str, layout| BoardingPass::parse(s, layout).map(|e| (e.row(), e.column())).unwrap();
+        let layout = SeatLayout::STANDARD;
+        assert_eq!(position("BFFFBBFRRR", layout), (70, 7));
+        assert_eq!(position("BBFFBBFRLL", layout), (102, 4));
+
+        let pass = BoardingPass::parse("BFBR", SeatLayout::new(8, 2).unwrap()).unwrap();
+        assert_eq!((pass.row(), pass.column(), pass.seat_id()), (5, 1, 11));
+        assert_eq!(position("FFFL", SeatLayout::new(8, 2).unwrap()), (0, 0));
+        assert_eq!(position("RL", SeatLayout::new(1, 4).unwrap()), (0, 2));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(BoardingPass::from_str("FBFBBFFRL").unwrap_err(), "pass \"FBFBBFFRL\" has 9 letters instead of 10");
+        assert_eq!(BoardingPass::from_str("FBFBBFRRLR").unwrap_err(),
+                   "pass \"FBFBBFRRLR\" has 'R' at position 7 instead of F or B");
+        assert_eq!(BoardingPass::from_str("FBFBBFFRLx").unwrap_err(),
+                   "pass \"FBFBBFFRLx\" has 'x' at position 10 instead of L or R");
+        assert!(BoardingPass::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_encode() {
+        let layout = SeatLayout::STANDARD;
+        assert_eq!(BoardingPass::new(70, 7, layout).unwrap().to_string(), "BFFFBBFRRR");
+        assert_eq!(BoardingPass::from_seat_id(820, layout).unwrap().to_string(), "BBFFBBFRLL");
+        assert_eq!(BoardingPass::new(128, 0, layout).unwrap_err(), "row 128 is outside 0..128");
+        assert_eq!(BoardingPass::from_seat_id(1024, layout).unwrap_err(), "seat ID 1024 is outside 0..1024");
+
+        let layout = SeatLayout::new(1, 4).unwrap();
+        assert_eq!(BoardingPass::new(0, 2, layout).unwrap().to_string(), "RL");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for &layout in &[SeatLayout::STANDARD, SeatLayout::new(4, 16).unwrap(), SeatLayout::new(1, 1).unwrap()] {
+            for id in layout.seat_ids() {
+                let pass = BoardingPass::from_seat_id(id, layout).unwrap();
+                let parsed = BoardingPass::parse(&pass.to_string(), layout).unwrap();
+                assert_eq!(parsed, pass);
+                assert_eq!(parsed.seat_id(), id);
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt::Write;
+
+use crate::layout::SeatLayout;
+use crate::pass::BoardingPass;
+
+/// A pass for a seat that was already taken, with the lines of both passes.
+#[derive(Debug, PartialEq)]
+pub struct Duplicate {
+    pub pass: BoardingPass,
+    pub line: usize,
+    pub first_line: usize,
+}
+
+/// Which seats of the cabin are taken, built from passes and the lines they were read from.
+pub struct SeatMap {
+    layout: SeatLayout,
+    /// Line of the first pass for every seat ID.
+    seats: Vec<Option<usize>>,
+    duplicates: Vec<Duplicate>,
+}
+
+impl SeatMap {
+    pub fn new(layout: SeatLayout, passes: impl IntoIterator<Item=(usize, BoardingPass)>) -> Self {
+        let mut seats = vec![None; layout.seat_ids().len()];
+        let mut duplicates = Vec::new();
+
+        for (line, pass) in passes {
+            match seats[pass.seat_id()] {
+                Some(first_line) => duplicates.push(Duplicate { pass, line, first_line }),
+                None => seats[pass.seat_id()] = Some(line),
+            }
+        }
+
+        SeatMap { layout, seats, duplicates }
+    }
+
+    fn is_occupied(&self, id: usize) -> bool {
+        self.seats[id].is_some()
+    }
+
+    /// Runs of consecutive free seat IDs as `(first ID, length)`.
+    pub fn gaps(&self) -> Vec<(usize, usize)> {
+        let mut gaps: Vec<(usize, usize)> = Vec::new();
+        for id in self.layout.seat_ids().filter(|&id| !self.is_occupied(id)) {
+            match gaps.last_mut() {
+                Some((start, len)) if *start + *len == id => *len += 1,
+                _ => gaps.push((id, 1)),
+            }
+        }
+        gaps
+    }
+
+    /// Free seats whose IDs are directly between two occupied ones.
+    pub fn enclosed_seats(&self) -> Vec<usize> {
+        self.layout.seat_ids()
+            .skip(1)
+            .filter(|&id| id + 1 < self.seats.len())
+            .filter(|&id| !self.is_occupied(id) && self.is_occupied(id - 1) && self.is_occupied(id + 1))
+            .collect()
+    }
+
+    pub fn duplicates(&self) -> &[Duplicate] {
+        &self.duplicates
+    }
+
+    /// One line per row, front first, with `#` for occupied seats, `.` for free ones and `*` for
+    /// the enclosed ones.
+    pub fn render(&self) -> String {
+        let enclosed = self.enclosed_seats();
+        let width = (self.layout.rows() - 1).to_string().len();
+
+        let mut result = String::new();
+        for row in 0..self.layout.rows() {
+            write!(result, "{:>width$} ", row, width = width).unwrap();
+            for column in 0..self.layout.columns() {
+                let id = self.layout.seat_id(row, column);
+                result.push(if self.is_occupied(id) {
+                    '#'
+                } else if enclosed.contains(&id) {
+                    '*'
+                } else {
+                    '.'
+                });
+            }
+            result.push('\n');
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seat_map(ids: &[usize]) -> SeatMap {
+        let layout = SeatLayout::new(4, 4).unwrap();
+        let passes = ids.iter().enumerate().map(|(i, &id)| (i + 1, BoardingPass::from_seat_id(id, layout).unwrap()));
+        SeatMap::new(layout, passes)
+    }
+
+    #[test]
+    fn test_gaps() {
+        let map = seat_map(&[2, 3, 4, 6, 8, 9, 10, 12, 13]);
+        assert_eq!(map.gaps(), vec![(0, 2), (5, 1), (7, 1), (11, 1), (14, 2)]);
+        assert_eq!(map.enclosed_seats(), vec![5, 7, 11]);
+        assert!(map.duplicates().is_empty());
+
+        assert_eq!(seat_map(&[]).gaps(), vec![(0, 16)]);
+        assert!(seat_map(&(0..16).collect::<Vec<_>>()).gaps().is_empty());
+    }
+
+    #[test]
+    fn test_edges_are_not_enclosed() {
+        assert!(seat_map(&[1, 14]).enclosed_seats().is_empty());
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let map = seat_map(&[3, 5, 3, 3]);
+        let lines: Vec<(usize, usize, usize)> = map.duplicates().iter()
+            .map(|e| (e.pass.seat_id(), e.line, e.first_line))
+            .collect();
+        assert_eq!(lines, vec![(3, 3, 1), (3, 4, 1)]);
+    }
+
+    #[test]
+    fn test_render() {
+        let map = seat_map(&[2, 3, 4, 6, 8, 9, 10, 12, 13]);
+        assert_eq!(map.render(), "0 ..##\n1 #*#*\n2 ###*\n3 ##..\n");
+    }
+}
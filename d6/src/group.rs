@@ -0,0 +1,131 @@
+/// Number of questions, `a` to `z`.
+pub const QUESTIONS: usize = 26;
+
+/// The questions one person answered, bit `i` standing for the letter `'a' + i`.
+pub type Answers = u32;
+
+/// Converts the letters of a person's line to a mask, anything but `a` to `z` is ignored.
+pub fn answers(person: &str) -> Answers {
+    person.chars()
+        .filter(|ch| ch.is_ascii_lowercase())
+        .fold(0, |acc, ch| acc | 1 << (ch as u32 - 'a' as u32))
+}
+
+/// The letters of the questions in the mask, in alphabetical order.
+pub fn questions(mask: Answers) -> impl Iterator<Item=char> {
+    (0..QUESTIONS as u32).filter(move |i| mask & 1 << i != 0).map(|i| (b'a' + i as u8) as char)
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Group {
+    pub people: Vec<Answers>,
+}
+
+impl Group {
+    /// Questions that anyone answered.
+    pub fn any(&self) -> Answers {
+        self.people.iter().fold(0, |acc, e| acc | e)
+    }
+
+    /// Questions that everyone answered, none for an empty group.
+    pub fn all(&self) -> Answers {
+        if self.people.is_empty() { return 0; }
+        self.people.iter().fold(!0, |acc, e| acc & e)
+    }
+
+    /// How many people answered each question.
+    pub fn counts(&self) -> [usize; QUESTIONS] {
+        let mut counts = [0; QUESTIONS];
+        for person in &self.people {
+            for (i, count) in counts.iter_mut().enumerate() {
+                *count += (person >> i & 1) as usize;
+            }
+        }
+        counts
+    }
+
+    /// Questions answered by at least `k` people. A question nobody answered never qualifies, so
+    /// `k == 0` behaves like `k == 1`.
+    pub fn at_least(&self, k: usize) -> Answers {
+        let k = k.max(1);
+        self.counts().iter()
+            .enumerate()
+            .filter(|(_, &count)| count >= k)
+            .fold(0, |acc, (i, _)| acc | 1 << i)
+    }
+
+    /// Questions answered by at least `percent` percent of the group, and by at least one person.
+    pub fn at_least_percent(&self, percent: u32) -> Answers {
+        let k = (self.people.len() * percent as usize).div_ceil(100);
+        self.at_least(k)
+    }
+}
+
+/// How often each question was answered over a set of groups.
+#[derive(Debug, PartialEq)]
+pub struct Frequencies {
+    /// Number of people that answered the question.
+    pub people: [usize; QUESTIONS],
+    /// Number of groups in which someone answered the question.
+    pub groups: [usize; QUESTIONS],
+}
+
+pub fn frequencies<'a>(groups: impl IntoIterator<Item=&'a Group>) -> Frequencies {
+    let mut result = Frequencies { people: [0; QUESTIONS], groups: [0; QUESTIONS] };
+    for group in groups {
+        let any = group.any();
+        for (i, count) in group.counts().iter().enumerate() {
+            result.people[i] += count;
+            result.groups[i] += (any >> i & 1) as usize;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb";
+
+    fn example() -> Vec<Group> {
+        EXAMPLE.split("\n\n")
+            .map(|group| Group { people: group.lines().map(answers).collect() })
+            .collect()
+    }
+
+    #[test]
+    fn test_any_and_all() {
+        let groups = example();
+        let any: u32 = groups.iter().map(|e| e.any().count_ones()).sum();
+        let all: u32 = groups.iter().map(|e| e.all().count_ones()).sum();
+        assert_eq!((any, all), (11, 6));
+
+        assert_eq!(Group::default().all(), 0);
+        assert_eq!(Group::default().any(), 0);
+    }
+
+    #[test]
+    fn test_at_least() {
+        let group = Group { people: vec![answers("abc"), answers("ab"), answers("a"), answers("z")] };
+        assert_eq!(questions(group.at_least(1)).collect::<String>(), "abcz");
+        assert_eq!(questions(group.at_least(2)).collect::<String>(), "ab");
+        assert_eq!(questions(group.at_least(3)).collect::<String>(), "a");
+        assert_eq!(group.at_least(5), 0);
+        assert_eq!(group.at_least(0), group.any());
+        assert_eq!(group.at_least(4), group.all());
+
+        assert_eq!(questions(group.at_least_percent(50)).collect::<String>(), "ab");
+        assert_eq!(questions(group.at_least_percent(51)).collect::<String>(), "a");
+        assert_eq!(group.at_least_percent(0), group.any());
+        assert_eq!(group.at_least_percent(100), group.all());
+        assert_eq!(Group::default().at_least_percent(50), 0);
+    }
+
+    #[test]
+    fn test_frequencies() {
+        let freq = frequencies(&example());
+        assert_eq!(&freq.people[..4], &[8, 4, 3, 0]);
+        assert_eq!(&freq.groups[..4], &[4, 4, 3, 0]);
+    }
+}
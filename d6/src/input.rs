@@ -0,0 +1,164 @@
+use std::fmt;
+use std::io::{self, BufRead};
+use std::str::FromStr;
+
+use crate::group::{answers, Answers, Group};
+
+/// The questions that may appear in the input, a subset of `a` to `z`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Alphabet(Answers);
+
+impl Alphabet {
+    pub const ALL: Alphabet = Alphabet((1 << 26) - 1);
+
+    fn contains(&self, ch: char) -> bool {
+        ch.is_ascii_lowercase() && self.0 >> (ch as u32 - 'a' as u32) & 1 != 0
+    }
+}
+
+impl FromStr for Alphabet {
+    type Err = String;
+
+    /// Parses letters and ranges of letters, e.g. `a-f` or `abcx-z`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("invalid question alphabet {:?}", s);
+        let mut chars = s.chars().peekable();
+        let mut mask = 0;
+        while let Some(first) = chars.next() {
+            let last = if chars.peek() == Some(&'-') {
+                chars.next();
+                chars.next().ok_or_else(error)?
+            } else {
+                first
+            };
+            if !first.is_ascii_lowercase() || !last.is_ascii_lowercase() || first > last {
+                return Err(error());
+            }
+            for ch in first..=last {
+                mask |= 1 << (ch as u32 - 'a' as u32);
+            }
+        }
+        if mask == 0 { return Err(error()); }
+        Ok(Alphabet(mask))
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    /// A character outside the alphabet. Lines and columns start from 1.
+    UnexpectedCharacter { line: usize, column: usize, ch: char },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "could not read input: {}", e),
+            ParseError::UnexpectedCharacter { line, column, ch } =>
+                write!(f, "line {}, column {}: unexpected character {:?}", line, column, ch),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
+/// Streams the groups of a reader. Groups are separated by any number of blank lines, surrounding
+/// whitespace of a line is ignored. A group with an unexpected character is returned as an error
+/// once all of its lines have been read, so that reading continues with the next group.
+pub struct Groups<R> {
+    lines: io::Lines<R>,
+    alphabet: Alphabet,
+    line: usize,
+}
+
+pub fn read_groups<R: BufRead>(reader: R, alphabet: Alphabet) -> Groups<R> {
+    Groups { lines: reader.lines(), alphabet, line: 0 }
+}
+
+impl<R: BufRead> Iterator for Groups<R> {
+    type Item = Result<Group, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut people = Vec::new();
+        let mut error = None;
+
+        for line in &mut self.lines {
+            self.line += 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(ParseError::from(e))),
+            };
+
+            let text = line.trim();
+            if text.is_empty() {
+                if people.is_empty() && error.is_none() { continue; }
+                break;
+            }
+
+            let (alphabet, number) = (self.alphabet, self.line);
+            if error.is_none() {
+                error = line.chars()
+                    .enumerate()
+                    .find(|&(_, ch)| !ch.is_whitespace() && !alphabet.contains(ch))
+                    .map(|(column, ch)| ParseError::UnexpectedCharacter { line: number, column: column + 1, ch });
+            }
+            people.push(answers(text));
+        }
+
+        match error {
+            Some(e) => Some(Err(e)),
+            None if people.is_empty() => None,
+            None => Some(Ok(Group { people })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(people: &[&str]) -> Group {
+        Group { people: people.iter().map(|e| answers(e)).collect() }
+    }
+
+    #[test]
+    fn test_alphabet() {
+        assert_eq!(Alphabet::from_str("a-z").unwrap(), Alphabet::ALL);
+        assert_eq!(Alphabet::from_str("abd-f").unwrap(), Alphabet(0b111011));
+        assert_eq!(Alphabet::from_str("x").unwrap(), Alphabet(1 << 23));
+        assert!(Alphabet::from_str("z-a").is_err());
+        assert!(Alphabet::from_str("A-Z").is_err());
+        assert!(Alphabet::from_str("a-").is_err());
+        assert!(Alphabet::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_read_groups() {
+        let input = "\n\nabc\r\n\r\n\r\na\n b \nc  \n\n\n\nab\nac\n\n";
+        let groups: Vec<Group> = read_groups(input.as_bytes(), Alphabet::ALL).map(|e| e.unwrap()).collect();
+        assert_eq!(groups, vec![group(&["abc"]), group(&["a", "b", "c"]), group(&["ab", "ac"])]);
+
+        assert!(read_groups("\n \n".as_bytes(), Alphabet::ALL).next().is_none());
+    }
+
+    #[test]
+    fn test_unexpected_characters() {
+        let input = "abc\nab1\nA\n\nfa\n\nb";
+        let alphabet = Alphabet::from_str("a-e").unwrap();
+        let results: Vec<String> = read_groups(input.as_bytes(), alphabet)
+            .map(|e| match e {
+                Ok(group) => format!("{:?}", group.people),
+                Err(e) => e.to_string(),
+            })
+            .collect();
+        assert_eq!(results, vec!["line 2, column 3: unexpected character '1'",
+                                 "line 5, column 1: unexpected character 'f'",
+                                 "[2]"]);
+    }
+}
@@ -1,35 +1,101 @@
-use std::collections::HashSet;
-
-fn read_data(filename: &str) -> Vec<Vec<String>> {
-    let buf = std::fs::read_to_string(filename).unwrap();
-    let groups = buf.split("\n\n");
-    groups.map(|group| group.split("\n")
-        .filter(|e| !e.is_empty())
-        .map(|e| e.to_owned())
-        .collect())
-        .collect()
+mod group;
+mod input;
+
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use group::{frequencies, questions, Group};
+use input::{read_groups, Alphabet, ParseError};
+
+/// Reads all groups, reporting and skipping the ones with unexpected characters. `-` reads stdin.
+fn read_data(input: &str, alphabet: Alphabet) -> Result<Vec<Group>, ParseError> {
+    let reader: Box<dyn BufRead> = if input == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(io::BufReader::new(File::open(input)?))
+    };
+
+    let mut groups = Vec::new();
+    for group in read_groups(reader, alphabet) {
+        match group {
+            Ok(group) => groups.push(group),
+            Err(ParseError::Io(e)) => return Err(ParseError::Io(e)),
+            Err(e) => eprintln!("{}: {}", input, e),
+        }
+    }
+    Ok(groups)
+}
+
+/// A threshold on how many people of a group answered a question.
+enum Quorum {
+    People(usize),
+    Percent(u32),
+}
+
+struct Options {
+    input: String,
+    alphabet: Alphabet,
+    quorum: Option<Quorum>,
+    frequencies: bool,
+}
+
+/// `--at-least K` or `--percent P` count the questions that at least `K` people or `P` percent of
+/// each group answered, `--frequencies` prints how often every question was answered. `--input FILE`
+/// reads another file than `input`, or stdin for `-`, and `--alphabet a-z` sets the valid questions.
+fn parse_args(mut args: impl Iterator<Item=String>) -> Options {
+    let mut options = Options { input: "input".to_owned(), alphabet: Alphabet::ALL, quorum: None, frequencies: false };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_default();
+        match arg.as_str() {
+            "--input" => options.input = value(),
+            "--alphabet" => options.alphabet = Alphabet::from_str(&value()).unwrap(),
+            "--at-least" => options.quorum = Some(Quorum::People(usize::from_str(&value()).unwrap())),
+            "--percent" => options.quorum = Some(Quorum::Percent(u32::from_str(&value()).unwrap())),
+            "--frequencies" => options.frequencies = true,
+            _ => panic!("Unknown argument: {}", arg),
+        }
+    }
+    options
 }
 
 fn main() {
-    let data = read_data("input");
-    let mut any_count = 0;
-    let mut all_count = 0;
-    for group in &data {
-        let sets: Vec<HashSet<char>> = group.iter()
-            .map(|person| person.chars().collect::<HashSet<char>>())
-            .collect();
+    let options = parse_args(std::env::args().skip(1));
+    let data = match read_data(&options.input, options.alphabet) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("{}: {}", options.input, e);
+            std::process::exit(1);
+        }
+    };
 
-        let union = sets.iter()
-            .fold(HashSet::new(),
-                  |acc, e| acc.union(&e).cloned().collect());
+    if options.frequencies {
+        let freq = frequencies(&data);
+        println!("question people groups");
+        for (i, question) in questions(!0).enumerate() {
+            println!("{:>8} {:>6} {:>6}", question, freq.people[i], freq.groups[i]);
+        }
+        return;
+    }
 
-        let intersection = sets
-            .iter()
-            .fold(sets[0].clone(),
-                  |acc, e| acc.intersection(&e).cloned().collect());
+    if let Some(quorum) = options.quorum {
+        let count: u32 = data.iter()
+            .map(|group| match quorum {
+                Quorum::People(k) => group.at_least(k),
+                Quorum::Percent(p) => group.at_least_percent(p),
+            })
+            .map(|e| e.count_ones())
+            .sum();
+        println!("Group question count for questions that reached the quorum: {}", count);
+        return;
+    }
 
-        any_count += union.len();
-        all_count += intersection.len();
+    let mut any_count = 0;
+    let mut all_count = 0;
+    for group in &data {
+        any_count += group.any().count_ones();
+        all_count += group.all().count_ones();
     }
 
     println!("Group question count for any answered questions: {}", any_count);
@@ -0,0 +1,149 @@
+use crate::rules::{BagId, BagRules};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Dot,
+    Json,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dot" => Some(Format::Dot),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Which part of the rules to export.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scope {
+    All,
+    /// The bag and everything inside it.
+    ReachableFrom(String),
+    /// The bag and everything that eventually contains it.
+    Reaching(String),
+}
+
+/// The selected bags sorted by name, so that exports of two rule sets can be diffed.
+fn select(rules: &BagRules, scope: &Scope) -> Vec<BagId> {
+    let with_bag = |bag_type: &str, others: Vec<&str>| -> Vec<BagId> {
+        rules.id(bag_type).into_iter().chain(others.into_iter().filter_map(|e| rules.id(e))).collect()
+    };
+
+    let mut selected = match scope {
+        Scope::All => (0..rules.len()).collect(),
+        Scope::ReachableFrom(bag_type) => with_bag(bag_type, rules.descendants(bag_type)),
+        Scope::Reaching(bag_type) => with_bag(bag_type, rules.ancestors(bag_type)),
+    };
+    selected.sort_unstable_by_key(|&id| rules.name(id));
+    selected
+}
+
+/// The rules between the selected bags, for each bag the bags directly inside it.
+fn edges(rules: &BagRules, selected: &[BagId]) -> Vec<(BagId, Vec<(usize, BagId)>)> {
+    selected.iter()
+        .map(|&id| {
+            let children = rules.children_of(id).iter()
+                .filter(|(_, child)| selected.contains(child))
+                .copied()
+                .collect();
+            (id, children)
+        })
+        .collect()
+}
+
+fn quoted(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Writes a Graphviz digraph with an edge from each bag to the bags inside it, labelled with counts.
+fn to_dot(rules: &BagRules, selected: &[BagId]) -> String {
+    let mut result = String::from("digraph bags {\n");
+    for (id, children) in edges(rules, selected) {
+        if children.is_empty() {
+            result.push_str(&format!("    {};\n", quoted(rules.name(id))));
+        }
+        for (count, child) in children {
+            result.push_str(&format!("    {} -> {} [label=\"{}\"];\n", quoted(rules.name(id)),
+                                     quoted(rules.name(child)), count));
+        }
+    }
+    result.push_str("}\n");
+    result
+}
+
+/// Writes a JSON object from each bag to the list of bags inside it, one bag per line.
+fn to_json(rules: &BagRules, selected: &[BagId]) -> String {
+    let lines: Vec<String> = edges(rules, selected).into_iter()
+        .map(|(id, children)| {
+            let children: Vec<String> = children.into_iter()
+                .map(|(count, child)| format!("{{\"bag\":{},\"count\":{}}}", quoted(rules.name(child)), count))
+                .collect();
+            format!("  {}: [{}]", quoted(rules.name(id)), children.join(", "))
+        })
+        .collect();
+
+    if lines.is_empty() {
+        "{}\n".to_owned()
+    } else {
+        format!("{{\n{}\n}}\n", lines.join(",\n"))
+    }
+}
+
+pub fn export(rules: &BagRules, format: Format, scope: &Scope) -> String {
+    let selected = select(rules, scope);
+    match format {
+        Format::Dot => to_dot(rules, &selected),
+        Format::Json => to_json(rules, &selected),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    use crate::rules::tests::EXAMPLE;
+
+    #[test]
+    fn test_dot() {
+        let rules = BagRules::from_str(EXAMPLE).unwrap();
+        assert_eq!(export(&rules, Format::Dot, &Scope::ReachableFrom("dark olive".to_owned())),
+                   "digraph bags {\n    \"dark olive\" -> \"faded blue\" [label=\"3\"];\n    \
+                    \"dark olive\" -> \"dotted black\" [label=\"4\"];\n    \"dotted black\";\n    \"faded blue\";\n}\n");
+    }
+
+    #[test]
+    fn test_json() {
+        let rules = BagRules::from_str(EXAMPLE).unwrap();
+        assert_eq!(export(&rules, Format::Json, &Scope::Reaching("shiny gold".to_owned())),
+                   "{\n  \"bright white\": [{\"bag\":\"shiny gold\",\"count\":1}],\n  \
+                    \"dark orange\": [{\"bag\":\"bright white\",\"count\":3}, {\"bag\":\"muted yellow\",\"count\":4}],\n  \
+                    \"light red\": [{\"bag\":\"bright white\",\"count\":1}, {\"bag\":\"muted yellow\",\"count\":2}],\n  \
+                    \"muted yellow\": [{\"bag\":\"shiny gold\",\"count\":2}],\n  \"shiny gold\": []\n}\n");
+        assert_eq!(export(&rules, Format::Json, &Scope::Reaching("unknown".to_owned())), "{}\n");
+    }
+
+    #[test]
+    fn test_all() {
+        let rules = BagRules::from_str(EXAMPLE).unwrap();
+        let dot = export(&rules, Format::Dot, &Scope::All);
+        assert_eq!(dot.lines().filter(|e| e.contains("->")).count(), 13);
+        assert!(dot.contains("    \"muted yellow\" -> \"faded blue\" [label=\"9\"];\n"));
+        assert_eq!(quoted("a \"b\"\\"), "\"a \\\"b\\\"\\\\\"");
+    }
+}
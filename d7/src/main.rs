@@ -1,189 +1,166 @@
-/// I am not really proud of this one.
+// I am not really proud of this one.
 
-extern crate regex;
+mod export;
+mod parser;
+mod paths;
+mod rules;
 
-use std::collections::{HashMap, HashSet};
-use std::iter::FromIterator;
+use std::fmt;
 use std::str::FromStr;
 
-use regex::Regex;
+use export::{export, Format, Scope};
+use paths::{all_chains, explain, shortest_chain};
+use rules::{BagRules, GraphError};
 
-fn read_data(filename: &str) -> Vec<String> {
-    let data = std::fs::read_to_string(filename).unwrap();
-    data.split("\n").map(|e| e.to_owned()).filter(|e| !e.is_empty()).collect()
+/// Reports an error of a subcommand and exits.
+fn fail(e: impl fmt::Display) -> ! {
+    eprintln!("{}", e);
+    std::process::exit(1);
 }
 
-#[derive(PartialEq, Debug)]
-struct BagCountEntry {
-    bag_type: String,
-    count: usize,
+/// `export dot|json [--from COLOUR | --to COLOUR]` prints the rules, or only the part reachable
+/// from or reaching the colour.
+fn run_export(rules: &BagRules, args: &[String]) {
+    let format = args.first().and_then(|e| Format::from_name(e)).expect("Expected export dot|json");
+    let colour = || {
+        let colour = args.get(2..).map(|e| e.join(" ")).filter(|e| !e.is_empty())
+            .unwrap_or_else(|| panic!("Expected export {} {} COLOUR", args[0], args[1]));
+        if rules.id(&colour).is_none() { fail(GraphError::UnknownBag(colour)); }
+        colour
+    };
+    let scope = match args.get(1).map(|e| e.as_str()) {
+        None => Scope::All,
+        Some("--from") => Scope::ReachableFrom(colour()),
+        Some("--to") => Scope::Reaching(colour()),
+        Some(other) => panic!("Unknown argument: {}", other),
+    };
+    print!("{}", export(rules, format, &scope));
 }
 
-fn parse_data_one(data: &Vec<String>) -> HashMap<String, Vec<BagCountEntry>> {
-    let line_regex: Regex = Regex::new(r"(\d+)\s(.+?) bags?(?:,\s|\.)").unwrap();
-
-    let mut m: HashMap<String, Vec<BagCountEntry>> = HashMap::new();
-    for line in data {
-        let mut splitted = line.split("contain").map(|e| e.trim());
-        let key = splitted.next().and_then(|e| e.strip_suffix(" bags")).unwrap();
-        let captures_iter = line_regex.captures_iter(splitted.next().unwrap());
-
-        for capture in captures_iter {
-            let count = usize::from_str(&capture[1]).unwrap();
-            let child_key = &capture[2];
-            let new_entry = BagCountEntry { bag_type: key.to_owned(), count };
-            match m.get_mut(child_key) {
-                Some(v) => { v.push(new_entry); }
-                None => { m.insert(child_key.to_owned(), vec!(new_entry)); }
-            }
-        }
-    }
-    m
+/// Splits `OUTER to INNER` into the two colours, each of which can be one or several arguments.
+fn two_colours(command: &str, args: &[String]) -> (String, String) {
+    let usage = || panic!("Expected {} OUTER to INNER", command);
+    let i = args.iter().position(|e| e == "to").unwrap_or_else(usage);
+    let (outer, inner) = (args[..i].join(" "), args[i + 1..].join(" "));
+    if outer.is_empty() || inner.is_empty() { usage(); }
+    (outer, inner)
 }
 
-fn count_one(bag_type: &str, bag_map: &HashMap<String, Vec<BagCountEntry>>) -> usize {
-    if let Some(parents) = bag_map.get(bag_type) {
-        let mut to_visit: HashSet<&String> = HashSet::from_iter(parents.iter().map(|e| &e.bag_type));
-        let mut visited: HashSet<&String> = HashSet::new();
-        while !to_visit.is_empty() {
-            let cur = to_visit.iter().next().unwrap().clone();
-            to_visit.remove(cur);
-            if let Some(parents) = bag_map.get(cur) {
-                to_visit.extend(parents.iter().map(|e| &e.bag_type));
+/// `shortest OUTER to INNER`, `chains OUTER to INNER [--limit N]` and `why COLOUR`.
+fn run_path_query(rules: &BagRules, command: &str, args: &[String]) {
+    match command {
+        "shortest" => {
+            let (outer, inner) = two_colours(command, args);
+            match shortest_chain(rules, &outer, &inner) {
+                Some(chain) => println!("{}", chain),
+                None => println!("{} cannot contain {}", outer, inner),
             }
-            visited.insert(cur);
         }
-        visited.len()
-    } else { 0 }
-}
-
-fn parse_data_two(data: &Vec<String>) -> HashMap<String, Vec<BagCountEntry>> {
-    let line_regex: Regex = Regex::new(r"(\d+)\s(.+?) bags?(?:,\s|\.)").unwrap();
-
-    let mut m: HashMap<String, Vec<BagCountEntry>> = HashMap::new();
-    for line in data {
-        let mut splitted = line.split("contain").map(|e| e.trim());
-        let key = splitted.next().and_then(|e| e.strip_suffix(" bags")).unwrap();
-        let captures_iter = line_regex.captures_iter(splitted.next().unwrap());
-
-        let mut children_vec: Vec<BagCountEntry> = Vec::new();
-        for capture in captures_iter {
-            let count = usize::from_str(&capture[1]).unwrap();
-            let child_key = &capture[2];
-            let new_entry = BagCountEntry { bag_type: child_key.to_owned(), count };
-            children_vec.push(new_entry);
+        "chains" => {
+            let (args, limit) = match args {
+                [rest @ .., flag, limit] if flag == "--limit" => (rest, usize::from_str(limit).unwrap()),
+                _ => (args, 100),
+            };
+            let (outer, inner) = two_colours(command, args);
+            for chain in all_chains(rules, &outer, &inner, limit) {
+                println!("{}", chain);
+            }
         }
-        m.insert(key.to_owned(), children_vec);
+        "why" => {
+            let colour = args.join(" ");
+            for e in explain(rules, &colour).unwrap_or_else(|e| fail(e)) {
+                println!("{} x {} (itself and {} inside) = {}", e.count, e.bag_type, e.inside, e.total);
+            }
+            // The contributions add up to the total, which is already checked for overflow.
+            match rules.total_contained(&colour) {
+                Ok(total) => println!("total {}", total),
+                Err(e) => fail(e),
+            }
+        }
+        _ => panic!("Unknown command: {}", command),
     }
-    m
 }
 
-fn count_two(bag_type: &str, bag_map: &HashMap<String, Vec<BagCountEntry>>) -> usize {
-    if let Some(entries) = bag_map.get(bag_type) {
-        let mut count = 0;
-        for entry in entries {
-            count += entry.count;
-            count += entry.count * count_two(&entry.bag_type, bag_map);
-        }
-        count
-    } else { 0 }
+/// Runs a query such as `descendants shiny gold`, colours can be given as one or several arguments.
+fn run_query(rules: &BagRules, command: &str, colour: &str) {
+    match command {
+        "ancestors" => rules.ancestors(colour).iter().for_each(|e| println!("{}", e)),
+        "descendants" => rules.descendants(colour).iter().for_each(|e| println!("{}", e)),
+        "children" => rules.direct_children(colour).iter().for_each(|(count, e)| println!("{} {}", count, e)),
+        "total" => match rules.total_contained(colour) {
+            Ok(total) => println!("{}", total),
+            Err(e) => fail(e),
+        },
+        _ => panic!("Unknown command: {}", command),
+    }
 }
 
 fn main() {
-    let data = read_data("input");
-    let m = parse_data_one(&data);
-    let c = count_one("shiny gold", &m);
-    println!("{} many bags can contain a shiny gold bag.", c);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let data = std::fs::read_to_string("input").unwrap();
+    let rules = match BagRules::from_str(&data) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some((command, colour)) = args.split_first() {
+        match command.as_str() {
+            "export" => run_export(&rules, colour),
+            "shortest" | "chains" | "why" => run_path_query(&rules, command, colour),
+            _ => run_query(&rules, command, &colour.join(" ")),
+        }
+        return;
+    }
 
-    let m2 = parse_data_two(&data);
-    let c2 = count_two("shiny gold", &m2);
-    println!("A shiny gold bag contains {} many bags.", c2);
+    let c = rules.ancestors("shiny gold").len();
+    println!("{} many bags can contain a shiny gold bag.", c);
 
+    match rules.total_contained("shiny gold") {
+        Ok(c2) => println!("A shiny gold bag contains {} many bags.", c2),
+        Err(e) => eprintln!("{}", e),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
     use super::*;
+    use rules::tests::EXAMPLE;
 
     #[test]
     fn test_count_one() {
-        let mut m: HashMap<String, Vec<BagCountEntry>> = HashMap::new();
-        let vec1 = vec![BagCountEntry { bag_type: "b".to_string(), count: 1 },
-                        BagCountEntry { bag_type: "c".to_owned(), count: 2 }];
-        let vec2 = vec![BagCountEntry { bag_type: "c".to_owned(), count: 1 }];
-        m.insert("a".to_owned(), vec1);
-        m.insert("b".to_owned(), vec2);
-
-        assert_eq!(count_one("a", &m), 2);
-        assert_eq!(count_one("b", &m), 1);
-        assert_eq!(count_one("c", &m), 0);
-    }
+        let rules = BagRules::from_str("b bags contain 1 a bag.\nc bags contain 2 a bags, 1 b bag.").unwrap();
 
-    #[test]
-    fn test_parse_data_one() {
-        let line = "light gold bags contain 2 light lime bags, 1 faded green bag, 3 clear olive bags, 2 dim bronze bags.";
-        let result = parse_data_one(&vec![line.to_owned()]);
-        let mut expected: HashMap<String, Vec<BagCountEntry>> = HashMap::new();
-        expected.insert("light lime".to_owned(),
-                        vec![BagCountEntry { bag_type: "light gold".to_owned(), count: 2 }]);
-        expected.insert("faded green".to_owned(),
-                        vec![BagCountEntry { bag_type: "light gold".to_owned(), count: 1 }]);
-        expected.insert("clear olive".to_owned(),
-                        vec![BagCountEntry { bag_type: "light gold".to_owned(), count: 3 }]);
-        expected.insert("dim bronze".to_owned(),
-                        vec![BagCountEntry { bag_type: "light gold".to_owned(), count: 2 }]);
-
-        assert_eq!(result, expected);
+        assert_eq!(rules.ancestors("a").len(), 2);
+        assert_eq!(rules.ancestors("b").len(), 1);
+        assert_eq!(rules.ancestors("c").len(), 0);
     }
 
     #[test]
-    fn test_parse_data_two() {
+    fn test_parse_line() {
         let line = "light gold bags contain 2 light lime bags, 1 faded green bag, 3 clear olive bags, 2 dim bronze bags.";
-        let result = parse_data_two(&vec![line.to_owned()]);
-        let mut expected: HashMap<String, Vec<BagCountEntry>> = HashMap::new();
-        expected.insert("light gold".to_owned(),
-                        vec![BagCountEntry { bag_type: "light lime".to_owned(), count: 2 },
-                             BagCountEntry { bag_type: "faded green".to_owned(), count: 1 },
-                             BagCountEntry { bag_type: "clear olive".to_owned(), count: 3 },
-                             BagCountEntry { bag_type: "dim bronze".to_owned(), count: 2 }]);
-
-        assert_eq!(result, expected);
+        let rules = BagRules::from_str(line).unwrap();
+
+        assert_eq!(rules.direct_children("light gold"),
+                   vec![(2, "light lime"), (1, "faded green"), (3, "clear olive"), (2, "dim bronze")]);
+        for child in &["light lime", "faded green", "clear olive", "dim bronze"] {
+            assert_eq!(rules.ancestors(child), vec!["light gold"]);
+            assert!(rules.direct_children(child).is_empty());
+        }
     }
 
     #[test]
     fn test_part_one() {
-        let data = r"light red bags contain 1 bright white bag, 2 muted yellow bags.
-dark orange bags contain 3 bright white bags, 4 muted yellow bags.
-bright white bags contain 1 shiny gold bag.
-muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
-shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
-dark olive bags contain 3 faded blue bags, 4 dotted black bags.
-vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
-faded blue bags contain no other bags.
-dotted black bags contain no other bags.";
-
-        let data_vec = data.split("\n").map(|e| e.to_owned()).collect();
-        let m = parse_data_one(&data_vec);
-        assert_eq!(count_one("shiny gold", &m), 4);
+        let rules = BagRules::from_str(EXAMPLE).unwrap();
+        assert_eq!(rules.ancestors("shiny gold").len(), 4);
     }
 
     #[test]
     fn test_part_two() {
-        let data = r"light red bags contain 1 bright white bag, 2 muted yellow bags.
-dark orange bags contain 3 bright white bags, 4 muted yellow bags.
-bright white bags contain 1 shiny gold bag.
-muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
-shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
-dark olive bags contain 3 faded blue bags, 4 dotted black bags.
-vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
-faded blue bags contain no other bags.
-dotted black bags contain no other bags.";
-
-        let data_vec = data.split("\n").map(|e| e.to_owned()).collect();
-        let m = parse_data_two(&data_vec);
-        assert_eq!(count_two("shiny gold", &m), 32);
+        let rules = BagRules::from_str(EXAMPLE).unwrap();
+        assert_eq!(rules.total_contained("shiny gold"), Ok(32));
     }
 
     #[test]
@@ -196,8 +173,16 @@ dark green bags contain 2 dark blue bags.
 dark blue bags contain 2 dark violet bags.
 dark violet bags contain no other bags.";
 
-        let data_vec = data.split("\n").map(|e| e.to_owned()).collect();
-        let m = parse_data_two(&data_vec);
-        assert_eq!(count_two("shiny gold", &m), 126);
+        let rules = BagRules::from_str(data).unwrap();
+        assert_eq!(rules.total_contained("shiny gold"), Ok(126));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_part_two_cycle() {
+        let data = r"shiny gold bags contain 2 dark red bags.
+dark red bags contain 1 shiny gold bag.";
+
+        assert_eq!(BagRules::from_str(data).err(),
+                   Some(GraphError::Cycle(vec!["shiny gold".to_owned(), "dark red".to_owned(), "shiny gold".to_owned()])));
+    }
+}
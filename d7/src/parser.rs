@@ -0,0 +1,220 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// A rule line that does not follow the grammar. Lines and columns start from 1.
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// One line of the rules, colours have their words joined by single spaces.
+#[derive(Debug, PartialEq)]
+pub struct Rule {
+    pub colour: String,
+    /// `(count, colour)` for each bag inside, empty for `no other bags`.
+    pub contents: Vec<(usize, String)>,
+}
+
+struct Token<'a> {
+    text: &'a str,
+    column: usize,
+}
+
+/// Splits at whitespace, with `,` and `.` as tokens of their own.
+fn tokenize(line: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    for (column, (i, ch)) in line.char_indices().enumerate() {
+        if ch.is_whitespace() || ch == ',' || ch == '.' {
+            if let Some((begin, begin_column)) = start.take() {
+                tokens.push(Token { text: &line[begin..i], column: begin_column });
+            }
+            if !ch.is_whitespace() {
+                tokens.push(Token { text: &line[i..i + 1], column: column + 1 });
+            }
+        } else if start.is_none() {
+            start = Some((i, column + 1));
+        }
+    }
+    if let Some((begin, begin_column)) = start {
+        tokens.push(Token { text: &line[begin..], column: begin_column });
+    }
+    tokens
+}
+
+fn is_bag(word: &str) -> bool {
+    word == "bag" || word == "bags"
+}
+
+fn is_colour_word(word: &str) -> bool {
+    !is_bag(word) && word.chars().all(|ch| ch.is_alphanumeric() || ch == '-')
+}
+
+/// Recursive descent over the grammar
+///
+/// ```text
+/// rule     = colour bags ("contain" | "contains") contents "."
+/// contents = "no" "other" bags | item ("," item)*
+/// item     = count colour bags
+/// bags     = "bag" | "bags"
+/// ```
+///
+/// where a colour is one or more words of letters, digits and hyphens. Either form of `bag` and
+/// `contain` is accepted everywhere, regardless of the count.
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    line: usize,
+    /// Column just after the end of the line, for errors about missing tokens.
+    end_column: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: String) -> ParseError {
+        let column = self.tokens.get(self.position).map_or(self.end_column, |e| e.column);
+        ParseError { line: self.line, column, message }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).map(|e| e.text)
+    }
+
+    fn found(&self) -> String {
+        self.peek().map_or_else(|| "the end of the line".to_owned(), |e| format!("{:?}", e))
+    }
+
+    fn expect(&mut self, description: &str, accepts: impl Fn(&str) -> bool) -> Result<&'a str, ParseError> {
+        match self.peek() {
+            Some(text) if accepts(text) => {
+                self.position += 1;
+                Ok(text)
+            }
+            _ => Err(self.error(format!("expected {}, found {}", description, self.found()))),
+        }
+    }
+
+    /// The words up to `bag` or `bags`, which is consumed as well.
+    fn colour(&mut self) -> Result<String, ParseError> {
+        let mut words = Vec::new();
+        loop {
+            match self.peek() {
+                Some(text) if is_bag(text) && !words.is_empty() => {
+                    self.position += 1;
+                    return Ok(words.join(" "));
+                }
+                Some(text) if is_colour_word(text) => {
+                    words.push(text);
+                    self.position += 1;
+                }
+                _ if words.is_empty() => return Err(self.error(format!("expected a colour, found {}", self.found()))),
+                _ => return Err(self.error(format!("expected \"bags\" after the colour, found {}", self.found()))),
+            }
+        }
+    }
+
+    fn item(&mut self) -> Result<(usize, String), ParseError> {
+        let column = self.tokens.get(self.position).map_or(self.end_column, |e| e.column);
+        let count = self.expect("a count", |e| e.chars().all(|ch| ch.is_ascii_digit()))?;
+        let count = usize::from_str(count)
+            .ok()
+            .filter(|&e| e > 0)
+            .ok_or_else(|| ParseError { line: self.line, column, message: format!("invalid count {:?}", count) })?;
+        Ok((count, self.colour()?))
+    }
+
+    fn rule(&mut self) -> Result<Rule, ParseError> {
+        let colour = self.colour()?;
+        self.expect("\"contain\"", |e| e == "contain" || e == "contains")?;
+
+        let mut contents = Vec::new();
+        if self.peek() == Some("no") {
+            self.position += 1;
+            self.expect("\"other\"", |e| e == "other")?;
+            self.expect("\"bags\"", is_bag)?;
+        } else {
+            if !self.peek().is_some_and(|e| e.starts_with(|ch: char| ch.is_ascii_digit())) {
+                return Err(self.error(format!("expected a count or \"no other bags\", found {}", self.found())));
+            }
+            contents.push(self.item()?);
+            while self.peek() == Some(",") {
+                self.position += 1;
+                contents.push(self.item()?);
+            }
+        }
+
+        self.expect("\",\" or \".\"", |e| e == ".")?;
+        if self.peek().is_some() {
+            return Err(self.error(format!("expected the end of the line, found {}", self.found())));
+        }
+        Ok(Rule { colour, contents })
+    }
+}
+
+/// Parses one rule, `line` is its line number for errors.
+pub fn parse_rule(text: &str, line: usize) -> Result<Rule, ParseError> {
+    let mut parser = Parser { tokens: tokenize(text), position: 0, line, end_column: text.chars().count() + 1 };
+    parser.rule()
+}
+
+/// Column of the first character that is not whitespace, where the outer colour starts.
+pub fn first_column(text: &str) -> usize {
+    text.chars().take_while(|ch| ch.is_whitespace()).count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(colour: &str, contents: &[(usize, &str)]) -> Rule {
+        Rule { colour: colour.to_owned(), contents: contents.iter().map(|&(n, e)| (n, e.to_owned())).collect() }
+    }
+
+    fn error(text: &str) -> String {
+        parse_rule(text, 7).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        assert_eq!(parse_rule("light red bags contain 1 bright white bag, 2 muted yellow bags.", 1),
+                   Ok(rule("light red", &[(1, "bright white"), (2, "muted yellow")])));
+        assert_eq!(parse_rule("faded blue bags contain no other bags.", 1), Ok(rule("faded blue", &[])));
+        assert_eq!(parse_rule("  faded   blue bag contains no other bag .  ", 1), Ok(rule("faded blue", &[])));
+        assert_eq!(parse_rule("a bags contain 2 b bag,3 c bags.", 1), Ok(rule("a", &[(2, "b"), (3, "c")])));
+    }
+
+    #[test]
+    fn test_contain_in_colour() {
+        assert_eq!(parse_rule("contain red bags contain 1 dark contain bag.", 1),
+                   Ok(rule("contain red", &[(1, "dark contain")])));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(error("faded blue bags contain no othr bags."), "line 7, column 28: expected \"other\", found \"othr\"");
+        assert_eq!(error("faded blue bags contain no other bags"),
+                   "line 7, column 38: expected \",\" or \".\", found the end of the line");
+        // Without "bags" the rest of the line reads as a colour, which may contain "contain".
+        assert_eq!(error("faded blue contain no other bags."), "line 7, column 33: expected \"contain\", found \".\"");
+        assert_eq!(error("faded blue contain nothing."),
+                   "line 7, column 27: expected \"bags\" after the colour, found \".\"");
+        assert_eq!(error("bags contain no other bags."), "line 7, column 1: expected a colour, found \"bags\"");
+        assert_eq!(error("a bags hold 1 b bag."), "line 7, column 8: expected \"contain\", found \"hold\"");
+        assert_eq!(error("a bags contain some b bags."),
+                   "line 7, column 16: expected a count or \"no other bags\", found \"some\"");
+        assert_eq!(error("a bags contain 0 b bags."), "line 7, column 16: invalid count \"0\"");
+        assert_eq!(error("a bags contain 1 b bag, c bags."), "line 7, column 25: expected a count, found \"c\"");
+        assert_eq!(error("a bags contain 1 b bag; 2 c bags."),
+                   "line 7, column 20: expected \"bags\" after the colour, found \"bag;\"");
+        assert_eq!(error("a bags contain 1 b bag. 2 c bags."),
+                   "line 7, column 25: expected the end of the line, found \"2\"");
+        assert_eq!(error("a bags contain 1 bag."), "line 7, column 18: expected a colour, found \"bag\"");
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::rules::{BagId, BagRules, GraphError};
+
+/// Bags that are each directly inside the previous one, from the outermost bag inward.
+#[derive(Debug, PartialEq)]
+pub struct Chain<'a> {
+    pub bags: Vec<&'a str>,
+    /// How many of the innermost bags one outermost bag holds along this chain, the product of the
+    /// counts of the rules. `None` if that does not fit in a `usize`.
+    pub count: Option<usize>,
+}
+
+impl fmt::Display for Chain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.bags.join(" -> "))?;
+        match self.count {
+            Some(1) => write!(f, " (1 bag)"),
+            Some(count) => write!(f, " ({} bags)", count),
+            None => write!(f, " (too many bags to count)"),
+        }
+    }
+}
+
+/// Builds the chain from each bag with the count of the rule that led to it, the first count is
+/// ignored. A rule can list the same inner bag more than once, so the counts cannot be looked up
+/// again from the bags alone.
+fn chain<'a>(rules: &'a BagRules, path: &[(BagId, usize)]) -> Chain<'a> {
+    let count = path.iter().skip(1).try_fold(1usize, |acc, &(_, count)| acc.checked_mul(count));
+    Chain { bags: path.iter().map(|&(id, _)| rules.name(id)).collect(), count }
+}
+
+/// The chain with the fewest bags from `outer` to `inner`, preferring earlier rules on ties.
+pub fn shortest_chain<'a>(rules: &'a BagRules, outer: &str, inner: &str) -> Option<Chain<'a>> {
+    let (start, end) = (rules.id(outer)?, rules.id(inner)?);
+
+    // The bag each bag was first reached from, with the count of that rule.
+    let mut previous: Vec<Option<(BagId, usize)>> = vec![None; rules.len()];
+    let mut queue: VecDeque<BagId> = VecDeque::new();
+    queue.push_back(start);
+    while let Some(id) = queue.pop_front() {
+        if id == end { break; }
+        for &(count, child) in rules.children_of(id) {
+            if child != start && previous[child].is_none() {
+                previous[child] = Some((id, count));
+                queue.push_back(child);
+            }
+        }
+    }
+
+    if start != end && previous[end].is_none() { return None; }
+    let mut path = vec![(end, 1)];
+    while let Some((id, count)) = previous[path.last().unwrap().0] {
+        path.last_mut().unwrap().1 = count;
+        path.push((id, 1));
+    }
+    path.reverse();
+    Some(chain(rules, &path))
+}
+
+/// Every chain from `outer` to `inner` in rule order, at most `limit` of them since their number
+/// can grow exponentially.
+pub fn all_chains<'a>(rules: &'a BagRules, outer: &str, inner: &str, limit: usize) -> Vec<Chain<'a>> {
+    let (start, end) = match (rules.id(outer), rules.id(inner)) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Vec::new(),
+    };
+
+    // Only bags that contain the inner bag can be on a chain.
+    let mut useful = vec![false; rules.len()];
+    useful[end] = true;
+    for bag_type in rules.ancestors(inner) {
+        useful[rules.id(bag_type).unwrap()] = true;
+    }
+
+    let mut result = Vec::new();
+    // The path as `(bag, count of the rule that led to it)` with the index of the next child of
+    // each bag. The rules are free of cycles, so the depth first search needs no visited set.
+    let mut path: Vec<(BagId, usize)> = vec![(start, 1)];
+    let mut next: Vec<usize> = vec![0];
+    while let Some(&(id, _)) = path.last() {
+        if result.len() >= limit { break; }
+        if id == end {
+            result.push(chain(rules, &path));
+            path.pop();
+            next.pop();
+            continue;
+        }
+
+        let i = next.last_mut().unwrap();
+        match rules.children_of(id).get(*i) {
+            Some(&(count, child)) => {
+                *i += 1;
+                if useful[child] {
+                    path.push((child, count));
+                    next.push(0);
+                }
+            }
+            None => {
+                path.pop();
+                next.pop();
+            }
+        }
+    }
+    result
+}
+
+/// What one directly contained bag adds to the total of the bag holding it.
+#[derive(Debug, PartialEq)]
+pub struct Contribution<'a> {
+    pub bag_type: &'a str,
+    pub count: usize,
+    /// Bags inside one of these bags.
+    pub inside: usize,
+    /// `count * (1 + inside)`.
+    pub total: usize,
+}
+
+/// Breaks `total_contained` of a bag down by the bags directly inside it.
+pub fn explain<'a>(rules: &'a BagRules, bag_type: &str) -> Result<Vec<Contribution<'a>>, GraphError> {
+    if rules.id(bag_type).is_none() { return Err(GraphError::UnknownBag(bag_type.to_owned())); }
+    rules.direct_children(bag_type).into_iter()
+        .map(|(count, child)| {
+            let inside = rules.total_contained(child)?;
+            let total = inside.checked_add(1)
+                .and_then(|e| e.checked_mul(count))
+                .ok_or_else(|| GraphError::Overflow(bag_type.to_owned()))?;
+            Ok(Contribution { bag_type: child, count, inside, total })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    use crate::rules::tests::EXAMPLE;
+
+    fn strings(chains: &[Chain]) -> Vec<String> {
+        chains.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_shortest_chain() {
+        let rules = BagRules::from_str(EXAMPLE).unwrap();
+        let chain = shortest_chain(&rules, "light red", "faded blue").unwrap();
+        assert_eq!(chain, Chain { bags: vec!["light red", "muted yellow", "faded blue"], count: Some(18) });
+
+        assert_eq!(shortest_chain(&rules, "light red", "dotted black").unwrap().to_string(),
+                   "light red -> bright white -> shiny gold -> dark olive -> dotted black (4 bags)");
+        assert_eq!(shortest_chain(&rules, "shiny gold", "shiny gold").unwrap().to_string(), "shiny gold (1 bag)");
+        assert!(shortest_chain(&rules, "faded blue", "light red").is_none());
+        assert!(shortest_chain(&rules, "unknown", "light red").is_none());
+    }
+
+    #[test]
+    fn test_all_chains() {
+        let rules = BagRules::from_str(EXAMPLE).unwrap();
+        assert_eq!(strings(&all_chains(&rules, "light red", "shiny gold", 10)),
+                   vec!["light red -> bright white -> shiny gold (1 bag)",
+                        "light red -> muted yellow -> shiny gold (4 bags)"]);
+
+        let chains = all_chains(&rules, "dark orange", "faded blue", 100);
+        assert_eq!(chains.len(), 5);
+        let total: usize = chains.iter().map(|e| e.count.unwrap()).sum();
+        assert_eq!(total, 3 * (3 + 10) + 4 * (2 * 13 + 9));
+
+        assert_eq!(all_chains(&rules, "dark orange", "faded blue", 2).len(), 2);
+        assert!(all_chains(&rules, "faded blue", "shiny gold", 10).is_empty());
+    }
+
+    #[test]
+    fn test_repeated_inner_bag() {
+        let rules = BagRules::from_str("a bags contain 1 b bag, 2 b bags.\nb bags contain 3 c bags.").unwrap();
+        assert_eq!(strings(&all_chains(&rules, "a", "c", 10)), vec!["a -> b -> c (3 bags)", "a -> b -> c (6 bags)"]);
+        assert_eq!(strings(&all_chains(&rules, "a", "b", 10)), vec!["a -> b (1 bag)", "a -> b (2 bags)"]);
+        assert_eq!(shortest_chain(&rules, "a", "c").unwrap().to_string(), "a -> b -> c (3 bags)");
+        assert_eq!(rules.total_contained("a"), Ok(3 * 4));
+    }
+
+    #[test]
+    fn test_explain() {
+        let rules = BagRules::from_str(EXAMPLE).unwrap();
+        let contributions = explain(&rules, "shiny gold").unwrap();
+        assert_eq!(contributions, vec![
+            Contribution { bag_type: "dark olive", count: 1, inside: 7, total: 8 },
+            Contribution { bag_type: "vibrant plum", count: 2, inside: 11, total: 24 },
+        ]);
+        let total: usize = contributions.iter().map(|e| e.total).sum();
+        assert_eq!(Ok(total), rules.total_contained("shiny gold"));
+        assert!(explain(&rules, "faded blue").unwrap().is_empty());
+        assert_eq!(explain(&rules, "shiny golf"), Err(GraphError::UnknownBag("shiny golf".to_owned())));
+    }
+}
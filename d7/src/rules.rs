@@ -0,0 +1,314 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::parser::{first_column, parse_rule, ParseError};
+
+#[derive(Debug, PartialEq)]
+pub enum GraphError {
+    /// A line that is not a rule, or a second rule for the same colour.
+    Parse(ParseError),
+    /// Bags that contain each other, the first bag is repeated at the end.
+    Cycle(Vec<String>),
+    /// The number of bags inside this bag does not fit in a `usize`.
+    Overflow(String),
+    /// No rule mentions this colour.
+    UnknownBag(String),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::Parse(e) => write!(f, "{}", e),
+            GraphError::Cycle(path) => write!(f, "bags contain themselves: {}", path.join(" -> ")),
+            GraphError::Overflow(bag_type) => write!(f, "too many bags inside {} to count", bag_type),
+            GraphError::UnknownBag(bag_type) => write!(f, "no rule mentions {} bags", bag_type),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+impl From<ParseError> for GraphError {
+    fn from(e: ParseError) -> Self {
+        GraphError::Parse(e)
+    }
+}
+
+/// Index of a bag colour in `BagRules`, in the order the colours first appear in the rules.
+pub type BagId = usize;
+
+/// All bag rules, checked to be free of cycles. Colours are interned, and the rules are kept in
+/// both directions together with the number of bags inside each bag.
+pub struct BagRules {
+    names: Vec<String>,
+    ids: HashMap<String, BagId>,
+    /// `(count, bag)` for the bags directly inside each bag, in rule order.
+    children: Vec<Vec<(usize, BagId)>>,
+    /// `(count, bag)` for the bags that directly contain each bag.
+    parents: Vec<Vec<(usize, BagId)>>,
+    /// `None` if the count overflowed.
+    totals: Vec<Option<usize>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Unvisited,
+    Visiting,
+    Done,
+}
+
+impl BagRules {
+    fn intern(&mut self, bag_type: &str) -> BagId {
+        if let Some(&id) = self.ids.get(bag_type) { return id; }
+
+        let id = self.names.len();
+        self.names.push(bag_type.to_owned());
+        self.ids.insert(bag_type.to_owned(), id);
+        self.children.push(Vec::new());
+        self.parents.push(Vec::new());
+        id
+    }
+
+    pub fn id(&self, bag_type: &str) -> Option<BagId> {
+        self.ids.get(bag_type).copied()
+    }
+
+    pub fn name(&self, id: BagId) -> &str {
+        &self.names[id]
+    }
+
+    /// Number of colours, IDs go from zero up to this.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// `(count, bag)` for the bags directly inside a bag, in rule order.
+    pub fn children_of(&self, id: BagId) -> &[(usize, BagId)] {
+        &self.children[id]
+    }
+
+    /// Orders the bags so that every bag comes after all bags it contains, or returns a cycle.
+    fn topological_order(&self) -> Result<Vec<BagId>, Vec<String>> {
+        let mut state = vec![State::Unvisited; self.names.len()];
+        let mut order = Vec::with_capacity(self.names.len());
+        for root in 0..self.names.len() {
+            if state[root] != State::Unvisited { continue; }
+
+            // The path from the root with the index of the next child to visit, instead of recursion.
+            let mut stack: Vec<(BagId, usize)> = vec![(root, 0)];
+            state[root] = State::Visiting;
+            while let Some(&(id, i)) = stack.last() {
+                let child = match self.children[id].get(i) {
+                    Some(&(_, child)) => child,
+                    None => {
+                        state[id] = State::Done;
+                        order.push(id);
+                        stack.pop();
+                        continue;
+                    }
+                };
+                stack.last_mut().unwrap().1 += 1;
+
+                match state[child] {
+                    State::Visiting => {
+                        let start = stack.iter().position(|e| e.0 == child).unwrap();
+                        let mut cycle: Vec<String> = stack[start..].iter().map(|e| self.names[e.0].clone()).collect();
+                        cycle.push(self.names[child].clone());
+                        return Err(cycle);
+                    }
+                    State::Done => {}
+                    State::Unvisited => {
+                        state[child] = State::Visiting;
+                        stack.push((child, 0));
+                    }
+                }
+            }
+        }
+
+        Ok(order)
+    }
+
+    /// Counts the bags inside every bag, each bag after the bags it contains.
+    fn compute_totals(&mut self) -> Result<(), GraphError> {
+        let order = self.topological_order().map_err(GraphError::Cycle)?;
+
+        let mut totals = vec![None; self.names.len()];
+        for id in order {
+            totals[id] = self.children[id].iter().try_fold(0usize, |acc, &(count, child)| {
+                let inside: usize = totals[child]?;
+                count.checked_mul(inside.checked_add(1)?)?.checked_add(acc)
+            });
+        }
+
+        self.totals = totals;
+        Ok(())
+    }
+
+    /// Bags that are directly inside a bag, with their counts.
+    pub fn direct_children(&self, bag_type: &str) -> Vec<(usize, &str)> {
+        self.id(bag_type)
+            .map_or(&[][..], |id| self.children[id].as_slice())
+            .iter()
+            .map(|&(count, child)| (count, self.name(child)))
+            .collect()
+    }
+
+    /// Every bag reachable through `edges` from the bag, sorted by name.
+    fn reachable(&self, bag_type: &str, edges: &[Vec<(usize, BagId)>]) -> Vec<&str> {
+        let start = match self.id(bag_type) {
+            Some(id) => id,
+            None => return Vec::new(),
+        };
+
+        let mut seen = vec![false; self.names.len()];
+        let mut queue: VecDeque<BagId> = VecDeque::new();
+        queue.push_back(start);
+        while let Some(id) = queue.pop_front() {
+            for &(_, next) in &edges[id] {
+                if !seen[next] {
+                    seen[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut result: Vec<&str> = (0..self.names.len()).filter(|&id| seen[id]).map(|id| self.name(id)).collect();
+        result.sort_unstable();
+        result
+    }
+
+    /// Bags that eventually contain the bag.
+    pub fn ancestors(&self, bag_type: &str) -> Vec<&str> {
+        self.reachable(bag_type, &self.parents)
+    }
+
+    /// Bags that are eventually inside the bag.
+    pub fn descendants(&self, bag_type: &str) -> Vec<&str> {
+        self.reachable(bag_type, &self.children)
+    }
+
+    /// Number of bags inside a bag.
+    pub fn total_contained(&self, bag_type: &str) -> Result<usize, GraphError> {
+        match self.id(bag_type).map(|id| self.totals[id]) {
+            Some(Some(total)) => Ok(total),
+            Some(None) => Err(GraphError::Overflow(bag_type.to_owned())),
+            None => Err(GraphError::UnknownBag(bag_type.to_owned())),
+        }
+    }
+}
+
+impl FromStr for BagRules {
+    type Err = GraphError;
+
+    /// Parses one rule per line, blank lines are skipped. Fails on malformed lines, on a second
+    /// rule for the same colour, and if any bag ends up inside itself.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = BagRules {
+            names: Vec::new(),
+            ids: HashMap::new(),
+            children: Vec::new(),
+            parents: Vec::new(),
+            totals: Vec::new(),
+        };
+
+        // Line of the rule for each colour that has one.
+        let mut defined: HashMap<BagId, usize> = HashMap::new();
+        for (i, text) in s.lines().enumerate().filter(|(_, e)| !e.trim().is_empty()) {
+            let rule = parse_rule(text, i + 1)?;
+            let id = rules.intern(&rule.colour);
+            if let Some(first_line) = defined.insert(id, i + 1) {
+                let message = format!("{} bags are already defined on line {}", rule.colour, first_line);
+                return Err(ParseError { line: i + 1, column: first_column(text), message }.into());
+            }
+
+            for (count, colour) in rule.contents {
+                let child = rules.intern(&colour);
+                rules.children[id].push((count, child));
+                rules.parents[child].push((count, id));
+            }
+        }
+
+        rules.compute_totals()?;
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// The example rules from the puzzle, shared with the tests of the other modules.
+    pub(crate) const EXAMPLE: &str = "light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+    #[test]
+    fn test_queries() {
+        let rules = BagRules::from_str(EXAMPLE).unwrap();
+        assert_eq!(rules.ancestors("shiny gold"), vec!["bright white", "dark orange", "light red", "muted yellow"]);
+        assert_eq!(rules.descendants("shiny gold"), vec!["dark olive", "dotted black", "faded blue", "vibrant plum"]);
+        assert_eq!(rules.direct_children("muted yellow"), vec![(2, "shiny gold"), (9, "faded blue")]);
+        assert!(rules.direct_children("faded blue").is_empty());
+        assert_eq!(rules.total_contained("shiny gold"), Ok(32));
+        assert_eq!(rules.total_contained("faded blue"), Ok(0));
+
+        assert!(rules.ancestors("unknown").is_empty());
+        assert!(rules.direct_children("unknown").is_empty());
+        assert_eq!(rules.total_contained("unknown"), Err(GraphError::UnknownBag("unknown".to_owned())));
+        assert_eq!(rules.total_contained("unknown").unwrap_err().to_string(), "no rule mentions unknown bags");
+    }
+
+    #[test]
+    fn test_interning() {
+        let rules = BagRules::from_str(EXAMPLE).unwrap();
+        let names: Vec<&str> = (0..5).map(|id| rules.name(id)).collect();
+        assert_eq!(names, vec!["light red", "bright white", "muted yellow", "dark orange", "shiny gold"]);
+        assert_eq!(rules.id("shiny gold"), Some(4));
+        assert_eq!(rules.parents[4], vec![(1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn test_cycles() {
+        let error = BagRules::from_str("a bags contain 1 b bag.\nb bags contain 1 a bag.").err().unwrap();
+        assert_eq!(error.to_string(), "bags contain themselves: a -> b -> a");
+
+        let error = BagRules::from_str("a bags contain 1 b bag.\nb bags contain 1 c bag.\nc bags contain 2 c bags.").err();
+        assert_eq!(error, Some(GraphError::Cycle(vec!["c".to_owned(), "c".to_owned()])));
+
+        let error = BagRules::from_str("x bags contain 1 y bag.\ny bags contain 1 z bag, 1 w bag.\nw bags contain 1 y bag.").err();
+        assert_eq!(error, Some(GraphError::Cycle(vec!["y".to_owned(), "w".to_owned(), "y".to_owned()])));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = BagRules::from_str("a bags contain 1 b bag.\n\nb bags contain no othr bags.").err().unwrap();
+        assert_eq!(error.to_string(), "line 3, column 19: expected \"other\", found \"othr\"");
+
+        let error = BagRules::from_str("a bags contain 1 b bag.\n b bag contains no other bags.\n  a bags contain no other bags.");
+        assert_eq!(error.err().unwrap().to_string(), "line 3, column 3: a bags are already defined on line 1");
+
+        let rules = BagRules::from_str("dark contain bags contain 1 light contain bag.\nlight contain bag contains 2 b bag.").unwrap();
+        assert_eq!(rules.direct_children("dark contain"), vec![(1, "light contain")]);
+        assert_eq!(rules.total_contained("dark contain"), Ok(3));
+    }
+
+    #[test]
+    fn test_shared_bags_and_overflow() {
+        // Every bag holds the next one twice, which takes 2^100 steps without memoisation.
+        let text: String = (0..100)
+            .map(|i| format!("bag{} bags contain 1 bag{} bag, 1 bag{} bag.\n", i, i + 1, i + 1))
+            .collect();
+
+        let rules = BagRules::from_str(&text).unwrap();
+        assert_eq!(rules.total_contained("bag95"), Ok(62));
+        assert_eq!(rules.total_contained("bag0"), Err(GraphError::Overflow("bag0".to_owned())));
+        assert_eq!(rules.total_contained("bag0").unwrap_err().to_string(), "too many bags inside bag0 to count");
+    }
+}